@@ -17,6 +17,9 @@ use hex;
 use base64;
 use rand::prelude::*;
 
+// Two hours of gameplay.
+const MAX_REPLAY_STEPS : u64 = 2 * 60 * 60 * 100;
+
 fn get_request() -> anyhow::Result<Request<String>> {
     const CONTENT_LENGTH : &'static str = "CONTENT_LENGTH";
     const HTTP_AUTHORIZATION : &'static str = "HTTP_AUTHORIZATION";
@@ -229,6 +232,50 @@ async fn new_score_http(request : &Request<NewScoreRequest>) -> anyhow::Result<R
         return Ok(response);
     }
 
+    if body.replay.seed != session_seed(decoded_session_id) {
+        let response = Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(NewScoreResponse::Error("The replay was not played with the seed of the session!".to_owned()))?;
+
+        return Ok(response);
+    }
+
+    if body.replay.num_steps() > MAX_REPLAY_STEPS {
+        let response = Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(NewScoreResponse::Error("The replay is too long!".to_owned()))?;
+
+        return Ok(response);
+    }
+
+    if let Some(level) = &body.replay.level {
+        if !gamelib::level::classic_levels()?.contains(level) {
+            let response = Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(NewScoreResponse::Error("Only the scores from the classic levels are accepted!".to_owned()))?;
+
+            return Ok(response);
+        }
+    }
+
+    if body.replay.config != gamelib::config::GameConfig::default() {
+        let response = Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(NewScoreResponse::Error("Only the scores played with the default rules are accepted!".to_owned()))?;
+
+        return Ok(response);
+    }
+
+    let simulation = gamelib::replay::simulate(&body.replay)?;
+
+    if simulation.score != body.score {
+        let response = Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(NewScoreResponse::Error("The score does not match the replay!".to_owned()))?;
+
+        return Ok(response);
+    }
+
     let id = Uuid::from_slice(&decoded_session_id[16..])?;
 
     let utc_now = Utc::now();
@@ -9,6 +9,8 @@ use tokio::sync::Semaphore;
 use http::{Response, StatusCode};
 use lazy_static::lazy_static;
 use apilib::*;
+use gamelib::*;
+use gamelib::replay::*;
 
 lazy_static! {
     static ref TEST_SEMAPHORE : Semaphore = Semaphore::new(1);
@@ -256,6 +258,31 @@ fn assert_json_eq(a : &str, b : &str) {
     assert_eq!(a.as_str(), b.as_str());
 }
 
+// Plays the game with the seed of the session and a bat following the ball until the score is
+// reached. The bat aims at different points of the ball until the score is hit exactly.
+fn replay_with_score(seed : u64, score : i64) -> Replay {
+    for aim in 0i32.. {
+        let aim_offset = ((aim * 37) % 160 - 80) as f32;
+        let mut simulation = Simulation::new(seed);
+        let mut replay = Replay::new(seed, GameMode::Classic);
+
+        // The ball can get stuck bouncing between the indestructible bricks.
+        while simulation.stage == GameStage::Gameplay && simulation.score < score && replay.num_steps() < 100000 {
+            let offset = (simulation.balls[0].position.x - simulation.bat.position.x + aim_offset) / 50f32;
+            let input = Input { direction: offset.max(-1f32).min(1f32) };
+
+            replay.record(&input);
+            step(&mut simulation, &input).unwrap();
+        }
+
+        if simulation.score == score {
+            return replay;
+        }
+    }
+
+    unreachable!();
+}
+
 #[tokio::test]
 async fn new_session_id_test() -> Result<(), Box<dyn std::error::Error>> {
     let actual : Response<Option<String>> = issue_api_request("new_session_id_test", "GET", "/api/session-id/new", r#""#)?;
@@ -317,6 +344,7 @@ async fn test_new_rename_api() -> Result<(), Box<dyn std::error::Error>> {
 
         let request = NewScoreRequest {
             score: 85i64,
+            replay: replay_with_score(session_seed(decoded_session_id), 85),
             session_id: session_id.body().as_ref().unwrap().clone(),
             proof_of_work: hex::encode_upper(proof_of_work),
             limit: 4i64
@@ -394,6 +422,7 @@ async fn test_new_rename_api_return_records_from_the_middle() -> Result<(), Box<
 
         let request = NewScoreRequest {
             score: 55i64,
+            replay: replay_with_score(session_seed(decoded_session_id), 55),
             session_id: session_id.body().as_ref().unwrap().clone(),
             proof_of_work: hex::encode_upper(proof_of_work),
             limit: 4i64
@@ -439,6 +468,9 @@ async fn test_new_rename_api_invalid_session_id() -> Result<(), Box<dyn std::err
 
         assert_eq!(StatusCode::OK, session_id.status());
 
+        let mut decoded_session_id = [0u8; 32];
+        hex::decode_to_slice(session_id.body().as_ref().unwrap(), &mut decoded_session_id)?;
+
         let session_id_amended = session_id.body().as_ref().unwrap()
             .char_indices()
             .map(|(i, c)| if i == 0 { if c == '0' { '1' } else { '0' } } else { c })
@@ -446,6 +478,7 @@ async fn test_new_rename_api_invalid_session_id() -> Result<(), Box<dyn std::err
 
         let request = NewScoreRequest {
             score: 85i64,
+            replay: replay_with_score(session_seed(decoded_session_id), 85),
             session_id: session_id_amended,
             proof_of_work: "".to_owned(),
             limit: 4i64
@@ -482,6 +515,7 @@ async fn test_new_rename_api_session_id_cannot_be_reused() -> Result<(), Box<dyn
 
         let request = NewScoreRequest {
             score: 85i64,
+            replay: replay_with_score(session_seed(decoded_session_id), 85),
             session_id: session_id.body().as_ref().unwrap().clone(),
             proof_of_work: hex::encode_upper(proof_of_work),
             limit: 4i64
@@ -520,8 +554,12 @@ async fn test_new_rename_api_invalid_proof_of_work() -> Result<(), Box<dyn std::
 
         assert_eq!(StatusCode::OK, session_id.status());
 
+        let mut decoded_session_id = [0u8; 32];
+        hex::decode_to_slice(session_id.body().as_ref().unwrap(), &mut decoded_session_id)?;
+
         let request = NewScoreRequest {
             score: 85i64,
+            replay: replay_with_score(session_seed(decoded_session_id), 85),
             session_id: session_id.body().as_ref().unwrap().clone(),
             proof_of_work: session_id.body().as_ref().unwrap().clone(),
             limit: 4i64
@@ -545,3 +583,156 @@ async fn test_new_rename_api_invalid_proof_of_work() -> Result<(), Box<dyn std::
     Ok(())
 }
 
+#[tokio::test]
+async fn test_new_rename_api_score_does_not_match_replay() -> Result<(), Box<dyn std::error::Error>> {
+    let body : &mut dyn FnMut(&Client) -> Result<(), Box<dyn std::error::Error>> = &mut |_| {
+        let session_id : Response<Option<String>> = issue_api_request("test_new_rename_api_score_does_not_match_replay", "GET", "/api/session-id/new", r#""#)?;
+
+        assert_eq!(StatusCode::OK, session_id.status());
+
+        let mut decoded_session_id = [0u8; 32];
+        hex::decode_to_slice(session_id.body().as_ref().unwrap(), &mut decoded_session_id)?;
+        let proof_of_work = proof_of_work(decoded_session_id, 42u64, 8);
+
+        let request = NewScoreRequest {
+            score: 85i64,
+            replay: replay_with_score(session_seed(decoded_session_id), 80),
+            session_id: session_id.body().as_ref().unwrap().clone(),
+            proof_of_work: hex::encode_upper(proof_of_work),
+            limit: 4i64
+        };
+
+        let request_json = serde_json::to_string(&request)?;
+
+        let actual : Response<Option<NewScoreResponse>> = issue_api_request(
+            "test_new_rename_api_score_does_not_match_replay",
+            "POST",
+            "/api/score/new",
+            request_json.as_str())?;
+
+        assert_eq!(StatusCode::BAD_REQUEST, actual.status());
+
+        return Ok(());
+    };
+
+    with_database("test_new_rename_api_score_does_not_match_replay", Some("SELECT * FROM insert_dummy_scores();"), body).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_new_rename_api_custom_level_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let body : &mut dyn FnMut(&Client) -> Result<(), Box<dyn std::error::Error>> = &mut |_| {
+        let session_id : Response<Option<String>> = issue_api_request("test_new_rename_api_custom_level_rejected", "GET", "/api/session-id/new", r#""#)?;
+
+        assert_eq!(StatusCode::OK, session_id.status());
+
+        let mut decoded_session_id = [0u8; 32];
+        hex::decode_to_slice(session_id.body().as_ref().unwrap(), &mut decoded_session_id)?;
+        let proof_of_work = proof_of_work(decoded_session_id, 42u64, 8);
+
+        let mut level = gamelib::level::classic_level("classic-1")?.unwrap();
+        level.ball_velocity *= 0.5f32;
+
+        let request = NewScoreRequest {
+            score: 0i64,
+            replay: Replay::with_level(session_seed(decoded_session_id), GameMode::Classic, Some(level)),
+            session_id: session_id.body().as_ref().unwrap().clone(),
+            proof_of_work: hex::encode_upper(proof_of_work),
+            limit: 4i64
+        };
+
+        let request_json = serde_json::to_string(&request)?;
+
+        let actual : Response<Option<NewScoreResponse>> = issue_api_request(
+            "test_new_rename_api_custom_level_rejected",
+            "POST",
+            "/api/score/new",
+            request_json.as_str())?;
+
+        assert_eq!(StatusCode::BAD_REQUEST, actual.status());
+
+        return Ok(());
+    };
+
+    with_database("test_new_rename_api_custom_level_rejected", Some("SELECT * FROM insert_dummy_scores();"), body).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_new_rename_api_custom_config_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let body : &mut dyn FnMut(&Client) -> Result<(), Box<dyn std::error::Error>> = &mut |_| {
+        let session_id : Response<Option<String>> = issue_api_request("test_new_rename_api_custom_config_rejected", "GET", "/api/session-id/new", r#""#)?;
+
+        assert_eq!(StatusCode::OK, session_id.status());
+
+        let mut decoded_session_id = [0u8; 32];
+        hex::decode_to_slice(session_id.body().as_ref().unwrap(), &mut decoded_session_id)?;
+        let proof_of_work = proof_of_work(decoded_session_id, 42u64, 8);
+
+        let mut config = gamelib::config::GameConfig::default();
+        config.num_lives = 10;
+
+        let request = NewScoreRequest {
+            score: 0i64,
+            replay: Replay::with_config(session_seed(decoded_session_id), GameMode::Classic, None, config),
+            session_id: session_id.body().as_ref().unwrap().clone(),
+            proof_of_work: hex::encode_upper(proof_of_work),
+            limit: 4i64
+        };
+
+        let request_json = serde_json::to_string(&request)?;
+
+        let actual : Response<Option<NewScoreResponse>> = issue_api_request(
+            "test_new_rename_api_custom_config_rejected",
+            "POST",
+            "/api/score/new",
+            request_json.as_str())?;
+
+        assert_eq!(StatusCode::BAD_REQUEST, actual.status());
+
+        return Ok(());
+    };
+
+    with_database("test_new_rename_api_custom_config_rejected", Some("SELECT * FROM insert_dummy_scores();"), body).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_new_rename_api_seed_does_not_match_session() -> Result<(), Box<dyn std::error::Error>> {
+    let body : &mut dyn FnMut(&Client) -> Result<(), Box<dyn std::error::Error>> = &mut |_| {
+        let session_id : Response<Option<String>> = issue_api_request("test_new_rename_api_seed_does_not_match_session", "GET", "/api/session-id/new", r#""#)?;
+
+        assert_eq!(StatusCode::OK, session_id.status());
+
+        let mut decoded_session_id = [0u8; 32];
+        hex::decode_to_slice(session_id.body().as_ref().unwrap(), &mut decoded_session_id)?;
+        let proof_of_work = proof_of_work(decoded_session_id, 42u64, 8);
+
+        let request = NewScoreRequest {
+            score: 0i64,
+            replay: Replay::new(session_seed(decoded_session_id).wrapping_add(1), GameMode::Classic),
+            session_id: session_id.body().as_ref().unwrap().clone(),
+            proof_of_work: hex::encode_upper(proof_of_work),
+            limit: 4i64
+        };
+
+        let request_json = serde_json::to_string(&request)?;
+
+        let actual : Response<Option<NewScoreResponse>> = issue_api_request(
+            "test_new_rename_api_seed_does_not_match_session",
+            "POST",
+            "/api/score/new",
+            request_json.as_str())?;
+
+        assert_eq!(StatusCode::BAD_REQUEST, actual.status());
+
+        return Ok(());
+    };
+
+    with_database("test_new_rename_api_seed_does_not_match_session", Some("SELECT * FROM insert_dummy_scores();"), body).await?;
+
+    Ok(())
+}
@@ -3,6 +3,7 @@ use std::convert::TryInto;
 use sha2::{Sha256, Digest};
 use serde::{Serialize, Deserialize};
 use rand::prelude::*;
+use gamelib::replay::Replay;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PlayerScore {
@@ -30,6 +31,7 @@ pub struct ListScoresResponse {
 #[derive(Serialize, Deserialize)]
 pub struct NewScoreRequest {
     pub score : i64,
+    pub replay : Replay,
     pub session_id : String,
     pub proof_of_work : String,
     pub limit : i64
@@ -60,6 +62,12 @@ pub fn rand256<T : RngCore + CryptoRng>(rng : &mut T) -> [u8; 32] {
     return result;
 }
 
+/// The seed of the game played in the session. It's taken from the random nonce of the session
+/// id, so the server chooses it and the replay submitted with the session has to be played with it.
+pub fn session_seed(session_id : [u8; 32]) -> u64 {
+    return u64::from_le_bytes(session_id[0..8].try_into().expect("wrong size"));
+}
+
 pub fn validate_proof_of_work(session_id : [u8; 32], proof_of_work : [u8; 32], degree : usize) -> (bool, [u8; 32]) {
     let sha256 = Sha256::new()
         .chain(session_id)
@@ -1,5 +1,7 @@
 use crate::utils::*;
+use crate::game::input::apply_dead_zone;
 
+use glm::*;
 use web_sys::*;
 use wasm_bindgen::JsCast;
 
@@ -48,7 +50,7 @@ impl TouchTracker {
                 closure: ClosureHandle::Empty
             }));
 
-        let closure = ClosureHandle::new({
+        let mut closure = ClosureHandle::new({
             let touch_tracker = std::rc::Rc::downgrade(&touch_tracker);
 
             Box::new(move |event : web_sys::TouchEvent| {
@@ -106,17 +108,12 @@ impl TouchTracker {
         let overlay : HtmlElement = document.get_element_by_id("main-overlay-id")
             .unwrap().unchecked_into();
 
-        overlay.add_event_listener_with_callback("touchstart", closure.function()).unwrap();
-        overlay.add_event_listener_with_callback("touchmove", closure.function()).unwrap();
-        overlay.add_event_listener_with_callback("touchend", closure.function()).unwrap();
-        overlay.add_event_listener_with_callback("touchcancel", closure.function()).unwrap();
-
         let body : HtmlElement = document.body().unwrap();
 
-        body.add_event_listener_with_callback("touchstart", closure.function()).unwrap();
-        body.add_event_listener_with_callback("touchmove", closure.function()).unwrap();
-        body.add_event_listener_with_callback("touchend", closure.function()).unwrap();
-        body.add_event_listener_with_callback("touchcancel", closure.function()).unwrap();
+        for event_type in ["touchstart", "touchmove", "touchend", "touchcancel"].iter() {
+            closure.listen(&overlay, event_type).unwrap();
+            closure.listen(&body, event_type).unwrap();
+        }
 
         touch_tracker.borrow_mut().closure = closure;
 
@@ -124,6 +121,211 @@ impl TouchTracker {
     }
 }
 
+/// Where the canvas is on the page. The canvas is letterboxed inside the `common-div`, so the
+/// client coordinates of the events are mapped through its bounding rectangle.
+pub struct CanvasGeometry {
+    /// The top left corner in the client coordinates.
+    pub origin : Vec2,
+    /// The size in the CSS pixels.
+    pub client_size : Vec2,
+    /// The size in the canvas pixels.
+    pub canvas_size : Vec2
+}
+
+impl CanvasGeometry {
+    /// `None` if there is no canvas or it isn't laid out yet.
+    pub fn get() -> Option<CanvasGeometry> {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas : HtmlCanvasElement = document.get_element_by_id("main-canvas-id")?.unchecked_into();
+        let rect = canvas.get_bounding_client_rect();
+
+        if rect.width() <= 0f64 || rect.height() <= 0f64 {
+            return None;
+        }
+
+        return Some(CanvasGeometry {
+            origin: vec2(rect.left() as f32, rect.top() as f32),
+            client_size: vec2(rect.width() as f32, rect.height() as f32),
+            canvas_size: vec2(canvas.width() as f32, canvas.height() as f32)
+        });
+    }
+
+    /// The canvas pixels per CSS pixel.
+    pub fn scale(&self) -> Vec2 {
+        self.canvas_size.component_div(&self.client_size)
+    }
+
+    pub fn contains(&self, client : Vec2) -> bool {
+        let local = client - self.origin;
+        return 0f32 <= local.x && 0f32 <= local.y && local.x <= self.client_size.x && local.y <= self.client_size.y;
+    }
+
+    pub fn to_canvas(&self, client : Vec2) -> Vec2 {
+        (client - self.origin).component_mul(&self.scale())
+    }
+}
+
+/// The last position of the mouse or the pen over the canvas, in the canvas pixels. While the
+/// pointer is locked the position is moved by the relative movements of the mouse instead.
+pub struct PointerTracker {
+    /// `None` until the pointer moves over the canvas.
+    pub position : Option<Vec2>,
+    pub canvas_size : Vec2,
+    pub closure : ClosureHandle
+}
+
+impl PointerTracker {
+    pub fn new() -> Rc<RefCell<PointerTracker>> {
+        let pointer_tracker = Rc::new(RefCell::new(
+            PointerTracker {
+                position: None,
+                canvas_size: vec2(0f32, 0f32),
+                closure: ClosureHandle::Empty
+            }));
+
+        let mut closure = ClosureHandle::new({
+            let pointer_tracker = std::rc::Rc::downgrade(&pointer_tracker);
+
+            Box::new(move |event : web_sys::PointerEvent| {
+                // The touches are handled by the `TouchTracker`.
+                if event.pointer_type() == "touch" {
+                    return;
+                }
+
+                let pointer_tracker = match pointer_tracker.upgrade() {
+                    Some(pointer_tracker) => pointer_tracker,
+                    None => return
+                };
+
+                let document = web_sys::window().unwrap().document().unwrap();
+
+                let geometry = match CanvasGeometry::get() {
+                    Some(geometry) => geometry,
+                    None => return
+                };
+
+                let canvas_size = geometry.canvas_size;
+                let client = vec2(event.client_x() as f32, event.client_y() as f32);
+                let mut pointer_tracker = pointer_tracker.borrow_mut();
+
+                let position = if document.pointer_lock_element().is_some() {
+                    let movement = vec2(event.movement_x() as f32, event.movement_y() as f32).component_mul(&geometry.scale());
+                    let position = pointer_tracker.position.unwrap_or(canvas_size * 0.5f32) + movement;
+                    vec2(position.x.max(0f32).min(canvas_size.x), position.y.max(0f32).min(canvas_size.y))
+                }
+                else if geometry.contains(client) {
+                    geometry.to_canvas(client)
+                }
+                else {
+                    return;
+                };
+
+                pointer_tracker.position = Some(position);
+                pointer_tracker.canvas_size = canvas_size;
+            })
+        });
+
+        let document = web_sys::window().unwrap().document().unwrap();
+        closure.listen(&document, "pointermove").unwrap();
+
+        pointer_tracker.borrow_mut().closure = closure;
+
+        return pointer_tracker;
+    }
+}
+
+/// The gamepads don't send events for the sticks and the buttons, they are polled once per frame.
+/// The gamepads plugged in or out during the game are picked up by the next poll.
+pub struct GamepadState {
+    /// The left stick x of the gamepad pushed the furthest, in the `[-1, 1]` range.
+    pub axis : f32,
+    /// The buttons held down on any of the gamepads.
+    pub buttons : Vec<u32>,
+    /// The buttons held down during the previous poll.
+    previous_buttons : Vec<u32>,
+    pub num_connected : u32,
+    /// Cleared when the browser fails to list the gamepads, they aren't polled again then.
+    supported : bool
+}
+
+impl GamepadState {
+    pub fn new() -> GamepadState {
+        GamepadState {
+            axis: 0f32,
+            buttons: Vec::new(),
+            previous_buttons: Vec::new(),
+            num_connected: 0,
+            supported: true
+        }
+    }
+
+    /// Reads the gamepads, the stick values inside the `dead_zone` count as zero.
+    pub fn poll(&mut self, dead_zone : f32) -> anyhow::Result<()> {
+        if !self.supported {
+            return Ok(());
+        }
+
+        let navigator = web_sys::window().unwrap().navigator();
+
+        let gamepads = match navigator.get_gamepads().to_anyhow() {
+            Ok(gamepads) => gamepads,
+            Err(error) => {
+                self.supported = false;
+                return Err(error);
+            }
+        };
+
+        self.previous_buttons = std::mem::take(&mut self.buttons);
+        self.axis = 0f32;
+
+        let mut num_connected = 0;
+
+        for gamepad in gamepads.iter() {
+            let gamepad = match gamepad.dyn_into::<web_sys::Gamepad>() {
+                Ok(gamepad) if gamepad.connected() => gamepad,
+                _ => continue
+            };
+
+            num_connected += 1;
+
+            if let Some(axis) = gamepad.axes().get(0).as_f64() {
+                let axis = apply_dead_zone(axis as f32, dead_zone);
+
+                if axis.abs() > self.axis.abs() {
+                    self.axis = axis;
+                }
+            }
+
+            for (index, button) in gamepad.buttons().iter().enumerate() {
+                if let Ok(button) = button.dyn_into::<web_sys::GamepadButton>() {
+                    if button.pressed() && !self.buttons.contains(&(index as u32)) {
+                        self.buttons.push(index as u32);
+                    }
+                }
+            }
+        }
+
+        if num_connected != self.num_connected {
+            log!("gamepads connected: {}", num_connected);
+            self.num_connected = num_connected;
+        }
+
+        return Ok(());
+    }
+
+    /// The buttons that went down since the previous poll.
+    pub fn pressed_buttons(&self) -> Vec<u32> {
+        self.buttons.iter().filter(|button| !self.previous_buttons.contains(button)).cloned().collect()
+    }
+}
+
+/// The identifier of the key, the `code` or the `key` when the code is empty, as the virtual
+/// keyboards of the phones often leave the code empty.
+pub fn key_code(event : &web_sys::KeyboardEvent) -> String {
+    let code = event.code();
+    return if code.is_empty() { event.key() } else { code };
+}
+
 pub struct KeyboardState {
     state : HashSet<String>,
     keydown_closure : ClosureHandle,
@@ -142,7 +344,7 @@ impl KeyboardState {
         let window = web_sys::window().unwrap();
         let document = window.document().unwrap();
 
-        let keydown_closure = ClosureHandle::new({
+        let mut keydown_closure = ClosureHandle::new({
             let keyboard_state = std::rc::Rc::downgrade(&keyboard_state);
 
             Box::new(move |event : web_sys::KeyboardEvent| {
@@ -151,14 +353,14 @@ impl KeyboardState {
                 match event {
                     Ok(event) => {
                         let keyboard_state = keyboard_state.upgrade().unwrap();
-                        keyboard_state.borrow_mut().state.insert(event.code());
+                        keyboard_state.borrow_mut().state.insert(key_code(&event));
                     },
                     _ => ()
                 }
             })
         });
 
-        let keyup_closure = ClosureHandle::new({
+        let mut keyup_closure = ClosureHandle::new({
             let keyboard_state = std::rc::Rc::downgrade(&keyboard_state);
 
             Box::new(move |event : web_sys::Event| {
@@ -167,30 +369,23 @@ impl KeyboardState {
                 match event {
                     Ok(event) => {
                         let keyboard_state = keyboard_state.upgrade().unwrap();
-                        keyboard_state.borrow_mut().state.remove(&event.code());
+                        keyboard_state.borrow_mut().state.remove(&key_code(&event));
                     },
                     _ => ()
                 }
             })
         });
 
+        keydown_closure.listen(&document, "keydown").unwrap();
+        keyup_closure.listen(&document, "keyup").unwrap();
+
         keyboard_state.borrow_mut().keydown_closure = keydown_closure;
         keyboard_state.borrow_mut().keyup_closure = keyup_closure;
 
-        document.add_event_listener_with_callback(
-            "keydown",
-            keyboard_state.borrow().keydown_closure.function())
-            .unwrap();
-
-        document.add_event_listener_with_callback(
-            "keyup",
-            keyboard_state.borrow().keyup_closure.function())
-            .unwrap();
-
         return keyboard_state;
     }
 
-    pub fn is_down(&self, code : &'static str) -> bool {
+    pub fn is_down(&self, code : &str) -> bool {
        self.state.contains(code)
     }
 }
@@ -0,0 +1,146 @@
+use crate::utils::*;
+use gamelib::audio::*;
+use web_sys::*;
+
+const VOLUME_KEY : &'static str = "volume";
+const MUTED_KEY : &'static str = "muted";
+const VOLUME_STEP : f32 = 0.1f32;
+
+/// Plays the synthesized sound effects through WebAudio. The volume and mute settings are kept
+/// in the local storage.
+pub struct AudioPlayer {
+    /// `None` if the browser doesn't support WebAudio, the game is silent then.
+    context : Option<AudioContext>,
+    gain : Option<GainNode>,
+    buffers : Vec<(SoundEffect, AudioBuffer)>,
+    pub volume : f32,
+    pub muted : bool
+}
+
+fn create_buffers(context : &AudioContext) -> anyhow::Result<Vec<(SoundEffect, AudioBuffer)>> {
+    let sample_rate = context.sample_rate();
+    let mut buffers = Vec::new();
+
+    for sound in &SOUNDS {
+        let mut samples = synthesize(sound.effect, sample_rate as u32);
+        let buffer = context.create_buffer(1, samples.len() as u32, sample_rate).to_anyhow()?;
+        buffer.copy_to_channel(&mut samples, 0).to_anyhow()?;
+        buffers.push((sound.effect, buffer));
+    }
+
+    return Ok(buffers);
+}
+
+fn load_settings() -> anyhow::Result<(f32, bool)> {
+    let storage = local_storage()?;
+    let volume = storage.get_item(VOLUME_KEY).to_anyhow()?
+        .and_then(|volume| volume.parse::<f32>().ok())
+        .unwrap_or(1f32);
+    let muted = storage.get_item(MUTED_KEY).to_anyhow()?.as_deref() == Some("true");
+
+    return Ok((volume.max(0f32).min(1f32), muted));
+}
+
+impl Default for AudioPlayer {
+    fn default() -> AudioPlayer {
+        AudioPlayer::new()
+    }
+}
+
+impl AudioPlayer {
+    pub fn new() -> AudioPlayer {
+        let (volume, muted) = load_settings().unwrap_or((1f32, false));
+
+        let mut player = AudioPlayer {
+            context: None,
+            gain: None,
+            buffers: Vec::new(),
+            volume: volume,
+            muted: muted
+        };
+
+        if let Err(error) = player.init() {
+            log!("Failed to initialize audio: {:?}", error);
+        }
+
+        return player;
+    }
+
+    fn init(&mut self) -> anyhow::Result<()> {
+        let context = AudioContext::new().to_anyhow()?;
+        let gain = context.create_gain().to_anyhow()?;
+        gain.connect_with_audio_node(&context.destination()).to_anyhow()?;
+
+        self.buffers = create_buffers(&context)?;
+        self.gain = Some(gain);
+        self.context = Some(context);
+        self.apply_volume();
+
+        return Ok(());
+    }
+
+    fn apply_volume(&self) {
+        if let Some(gain) = &self.gain {
+            gain.gain().set_value(if self.muted { 0f32 } else { self.volume });
+        }
+    }
+
+    fn save_settings(&self) -> anyhow::Result<()> {
+        let storage = local_storage()?;
+        storage.set_item(VOLUME_KEY, self.volume.to_string().as_str()).to_anyhow()?;
+        storage.set_item(MUTED_KEY, if self.muted { "true" } else { "false" }).to_anyhow()?;
+        return Ok(());
+    }
+
+    fn settings_changed(&self) {
+        self.apply_volume();
+
+        if let Err(error) = self.save_settings() {
+            log!("Failed to save audio settings: {:?}", error);
+        }
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        self.settings_changed();
+    }
+
+    /// Changes the volume by `steps` steps, a change of the volume also unmutes the sound.
+    pub fn change_volume(&mut self, steps : i32) {
+        self.volume = (self.volume + steps as f32 * VOLUME_STEP).max(0f32).min(1f32);
+        self.muted = false;
+        self.settings_changed();
+    }
+
+    /// The browsers start the audio suspended until the user interacts with the page.
+    pub fn unlock(&self) {
+        if let Some(context) = &self.context {
+            if context.state() == AudioContextState::Suspended {
+                let _ = context.resume();
+            }
+        }
+    }
+
+    pub fn play(&self, effect : SoundEffect) -> anyhow::Result<()> {
+        let (context, gain) = match (&self.context, &self.gain) {
+            (Some(context), Some(gain)) => (context, gain),
+            _ => return Ok(())
+        };
+
+        if self.muted {
+            return Ok(());
+        }
+
+        let buffer = match self.buffers.iter().find(|(buffer_effect, _)| *buffer_effect == effect) {
+            Some((_, buffer)) => buffer,
+            None => return Ok(())
+        };
+
+        let source = context.create_buffer_source().to_anyhow()?;
+        source.set_buffer(Some(buffer));
+        source.connect_with_audio_node(gain).to_anyhow()?;
+        source.start().to_anyhow()?;
+
+        return Ok(());
+    }
+}
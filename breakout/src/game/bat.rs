@@ -1,47 +1,26 @@
-use glm::*;
+use gamelib::bat::*;
+use crate::renderer::*;
 
-fn mul(a: Vec2, b: Vec2) -> Vec2 {
-    vec2(a.x * b.x, a.y * b.y)
-}
-
-pub fn fmin(a: f32, b: f32) -> f32 { if a < b { a } else { b } }
-pub fn fmax(a: f32, b: f32) -> f32 { if a < b { b } else { a } }
+/// The time the bat takes to slow down when it approaches the pointer, so it doesn't overshoot.
+pub const POINTER_SMOOTHING_TIME : f32 = 0.05f32;
 
-pub struct Bat {
-    pub position : Vec2,
-    pub velocity : Vec2,
-    pub size : Vec2,
-    pub input : Vec2
+pub fn render_bat(bat : &Bat, renderer : &mut dyn Renderer) -> anyhow::Result<()> {
+    let origin = bat.position - bat.size * 0.5;
+    renderer.fill_rect(origin, bat.size, "black")?;
+    return Ok(());
 }
 
-impl Bat {
-    pub fn new() -> Bat {
-        let bat_position = vec2(726.0 * 0.5, 868.0);
+/// The input direction that moves the bat towards the `target` x. The bat goes at full speed
+/// when it's far from the target and slows down over the last `POINTER_SMOOTHING_TIME`, it is
+/// never faster than with the keyboard.
+pub fn steer_bat(bat : &Bat, target : f32) -> f32 {
+    let (lo, hi) = (-1f32, 1f32);
+    let direction = (target - bat.position.x) / (bat.velocity.x * POINTER_SMOOTHING_TIME);
 
-        Bat {
-            position: bat_position,
-            velocity: vec2(1000.0, 1000.0),
-            size: vec2(200.0, 20.0),
-            input: vec2(0.0, 0.0)
-        }
+    // The clamp panics on the inverted bounds and passes the NaN through.
+    if lo > hi || direction.is_nan() {
+        return 0f32;
     }
-}
-
-pub fn update_bat(
-    bat : &mut Bat,
-    elapsed : f32) -> anyhow::Result<()> {
 
-    bat.position += mul(bat.input * elapsed, bat.velocity);
-
-    bat.position.x -= fmin(bat.position.x - bat.size.x * 0.5, 0f32);
-    bat.position.x -= fmax(bat.position.x + bat.size.x * 0.5 - 726.0, 0f32);
-
-    return Ok(());
+    return direction.clamp(lo, hi);
 }
-
-pub fn render_bat(bat : &Bat, rendering_context : &web_sys::CanvasRenderingContext2d) -> anyhow::Result<()> {
-    let origin = bat.position - bat.size * 0.5;
-    rendering_context.set_fill_style(&wasm_bindgen::JsValue::from_str("black"));
-    rendering_context.fill_rect(origin.x as f64, origin.y as f64, bat.size.x as f64, bat.size.y as f64);
-    return Ok(());
-}
\ No newline at end of file
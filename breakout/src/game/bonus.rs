@@ -0,0 +1,19 @@
+use gamelib::bonus::*;
+use crate::renderer::*;
+
+pub fn bonus_color(kind : BonusKind) -> &'static str {
+    match kind {
+        BonusKind::WidenBat => "green",
+        BonusKind::ShortenBat => "orange",
+        BonusKind::DoublePoints => "gold",
+        BonusKind::SlowDown => "blue",
+        BonusKind::SpeedUp => "red",
+        BonusKind::Blockage => "purple",
+        BonusKind::ExtraBall => "deeppink"
+    }
+}
+
+pub fn render_bonus(bonus : &Bonus, renderer : &mut dyn Renderer) -> anyhow::Result<()> {
+    renderer.fill_circle(bonus.position, bonus.size, bonus_color(bonus.kind))?;
+    return Ok(());
+}
@@ -1,192 +1,60 @@
-use glm::*;
-use crate::game::config;
-use std::iter::Iterator;
+use gamelib::bricks::*;
+use gamelib::config;
+use crate::renderer::*;
+use crate::game::bonus::*;
 
-fn mul(a: Vec2, b: Vec2) -> Vec2 {
-    vec2(a.x * b.x, a.y * b.y)
-}
-
-pub struct Brick {
-    pub position : Vec2,
-    pub size : Vec2,
-    pub destruction_time : Option<f32>,
-}
-
-impl Brick {
-    pub fn new(
-        position : Vec2,
-        size : Vec2) -> Brick {
-        Brick {
-            position: position,
-            size: size,
-            destruction_time: None
-        }
-    }
-}
-
-pub struct BrickConfig {
-    pub origin : Vec2,
-    pub size : Vec2,
-    pub width : f32,
-    pub height : f32,
-    pub col_width : f32,
-    pub row_height : f32,
-    pub spacing : Vec2
-}
-
-impl BrickConfig {
-    pub fn new() -> BrickConfig {
-        let game_area = vec2(config::GAME_AREA_WIDTH as f32, config::GAME_AREA_HEIGHT as f32);
-
-        let bricks_cols = config::NUM_BRICK_COLS;
-        let brick_spacing = config::BRICK_SPACING;
-        let brick_width = ((game_area.x - brick_spacing) / bricks_cols as f32 - brick_spacing).floor();
-        let brick_height = (brick_width * 0.5f32).floor();
-
-        return BrickConfig {
-            origin: vec2(brick_width, brick_height) * 0.5f32,
-            size: vec2(brick_width, brick_height),
-            width: brick_width,
-            height: brick_height,
-            col_width: brick_width + brick_spacing,
-            row_height: brick_height + brick_spacing,
-            spacing: vec2(brick_spacing, brick_spacing)
-        };
-    }
+fn fmin(a: f32, b: f32) -> f32 { if a < b { a } else { b } }
 
-    pub fn grid_position(&self, x : u32, y : u32) -> Vec2 {
-        let index = vec2(x as f32, y as f32);
-        return mul(self.size + self.spacing, index) + self.origin + self.spacing;
+pub fn brick_color(kind : BrickKind) -> &'static str {
+    match kind {
+        BrickKind::Normal => "black",
+        BrickKind::Strong => "dimgray",
+        BrickKind::Indestructible => "slategray",
+        BrickKind::Explosive => "darkorange"
     }
 }
 
-pub struct Bricks {
-    pub bricks : Vec<Brick>,
-    pub origin : Vec2,
-    pub num_cols : u32,
-    pub num_rows : u32,
-    pub row_shift : u32,
-    pub brick_config : BrickConfig
-}
-
-impl Bricks {
-    pub fn new() -> Bricks {
-        let mut bricks : Vec<Brick> = vec![];
-
-        let bricks_cols = config::NUM_BRICK_COLS;
-        let bricks_rows = config::NUM_BRICK_ROWS;
-        let brick_config = BrickConfig::new();
-
-        let brick_size = vec2(
-            brick_config.width,
-            brick_config.height);
-
-        let brick_origin = brick_size * 0.5;
-
-        for y in 0..bricks_rows {
-            for x in 0..bricks_cols {
-                let index = vec2(x as f32, y as f32);
-
-                let brick = Brick::new(
-                    brick_config.spacing + mul(brick_size + brick_config.spacing, index) + brick_origin,
-                    brick_size
-                );
-
-                bricks.push(brick);
-            }
-        }
+pub fn render_brick(brick : &Brick, renderer : &mut dyn Renderer) -> anyhow::Result<()> {
+    let size = brick.size * (1f32 - fmin(1f32, brick.destruction_time.unwrap_or(0f32) / config::BRICK_DESTRUCTION_TIME));
 
-        return Bricks {
-            bricks: bricks,
-            origin: vec2(0f32, 0f32),
-            num_cols: bricks_cols,
-            num_rows: bricks_rows,
-            row_shift: 0,
-            brick_config: brick_config
-        };
-    }
-
-    pub fn last_row(&self) -> u32 {
-        (self.row_shift + self.num_rows - 1) % self.num_rows
-    }
-
-    pub fn last_row_empty(&self) -> bool {
-        let last_row = self.last_row();
-        let begin = (last_row * self.num_cols) as usize;
-        let end = begin + self.num_cols as usize;
-        return self.bricks[begin..end].iter().all(|brick| brick.destruction_time.is_some());
-    }
-
-    pub fn reset_last_row(&mut self) {
-        self.row_shift = self.last_row();
-        self.origin += vec2(0f32, -self.brick_config.row_height);
-        let begin = (self.row_shift * self.num_cols) as usize;
-        let end = begin + self.num_cols as usize;
-
-        let mut i : usize = 0;
-        for brick in &mut self.bricks[begin..end] {
-            brick.position = self.origin + self.brick_config.grid_position(i as u32, 0);
-            brick.destruction_time = None;
-            i += 1;
-        }
-    }
-
-    pub fn update(
-        &mut self,
-        elapsed : f32) -> anyhow::Result<()> {
-
-        let mut should_fall = false;
-        let mut should_reset = false;
-        let mut offset = 0f32;
-
-        if self.origin.y != 0f32 {
-            offset = config::BRICKS_FALLING_VELOCITY * elapsed;
-            should_fall = true;
-
-            if self.origin.y + offset >= 0f32 {
-                self.origin.y = 0f32;
-                should_reset = true;
-            }
-            else {
-                self.origin.y += offset;
-            }
-        }
-
-        for brick in &mut self.bricks {
-            if let Some(destruction_time) = brick.destruction_time {
-                brick.destruction_time = Some(destruction_time + elapsed);
-            }
-
-            if should_fall {
-                if should_reset {
+    let origin = brick.position - size * 0.5;
 
-                }
-                else {
-                    brick.position.y += offset;
-                }
-            }
-        }
+    let color = match brick.destruction_time {
+        Some(_) => "red",
+        None => brick_color(brick.kind)
+    };
 
-        if self.last_row_empty() {
-            self.reset_last_row();
-        }
+    renderer.fill_rect(origin, size, color)?;
 
+    if brick.destruction_time.is_some() {
         return Ok(());
     }
-}
-
-fn fmin(a: f32, b: f32) -> f32 { if a < b { a } else { b } }
 
-pub fn render_brick(brick : &Brick, rendering_context : &web_sys::CanvasRenderingContext2d) -> anyhow::Result<()> {
-    let size = brick.size * (1f32 - fmin(1f32, brick.destruction_time.unwrap_or(0f32)));
+    let mut bonus_position = brick.position;
 
-    let origin = brick.position - size * 0.5;
+    match brick.kind {
+        BrickKind::Strong => {
+            renderer.text(brick.hit_points.to_string().as_str(), brick.position, brick.size.y * 0.6, "white")?;
+            bonus_position.x -= brick.size.x * 0.3;
+        },
+        BrickKind::Indestructible => {
+            let inset = brick.size.y * 0.2;
+            renderer.stroke_rect(
+                origin + glm::vec2(inset, inset),
+                size - glm::vec2(inset, inset) * 2f32,
+                2f32,
+                "black")?;
+        },
+        BrickKind::Explosive => {
+            renderer.fill_circle(brick.position, brick.size.y * 0.3, "red")?;
+            bonus_position.x -= brick.size.x * 0.3;
+        },
+        BrickKind::Normal => {}
+    }
 
-    match brick.destruction_time {
-        Some(_) => rendering_context.set_fill_style(&wasm_bindgen::JsValue::from_str("red")),
-        None => rendering_context.set_fill_style(&wasm_bindgen::JsValue::from_str("black"))
+    if let Some(kind) = brick.bonus {
+        renderer.fill_circle(bonus_position, config::BONUS_SIZE * 0.5, bonus_color(kind))?;
     }
 
-    rendering_context.fill_rect(origin.x as f64, origin.y as f64, size.x as f64, size.y as f64);
     return Ok(());
 }
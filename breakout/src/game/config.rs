@@ -1,19 +0,0 @@
-
-pub const PI : f32 = 3.141592;
-
-pub const GAME_AREA_WIDTH : f64 = 726f64;
-pub const GAME_AREA_HEIGHT : f64 = 968f64;
-
-pub const BALL_VELOCITY : f32 = 1000f32;
-pub const BALL_START_SIZE : f32 = 19f32;
-pub const BALL_START_X : f32 = 0.5f32;
-pub const BALL_START_Y : f32 = 0.75f32;
-pub const BALL_START_ANGLE : f32 = PI * 0.7f32;
-pub const BALL_FREEZE_TIME : f32 = 1f32;
-
-pub const BRICKS_FALLING_VELOCITY : f32 = 91f32;
-pub const NUM_BRICK_COLS : u32 = 10;
-pub const NUM_BRICK_ROWS : u32 = 10;
-pub const BRICK_SPACING : f32 = 10f32;
-
-pub const GAME_OVER_PAUSE_TIME : f64 = 2f64;
@@ -0,0 +1,239 @@
+use crate::utils::*;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+const KEY_BINDINGS_KEY : &'static str = "key-bindings";
+
+/// What the player wants to do, independent of the key, the touch or the click that asked for it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    /// Sends the ball again after a pause.
+    Launch,
+    Pause,
+    Confirm
+}
+
+/// The actions in the order they are listed in the controls menu, with their names.
+pub const ACTIONS : [(Action, &'static str); 5] = [
+    (Action::MoveLeft, "Move left"),
+    (Action::MoveRight, "Move right"),
+    (Action::Launch, "Launch"),
+    (Action::Pause, "Pause"),
+    (Action::Confirm, "Confirm")
+];
+
+pub fn action_name(action : Action) -> &'static str {
+    ACTIONS.iter().find(|(a, _)| *a == action).map_or("", |(_, name)| name)
+}
+
+/// Finds the action by the name of its variant, e.g. `MoveLeft`.
+pub fn parse_action(name : &str) -> Option<Action> {
+    ACTIONS.iter().map(|(action, _)| *action).find(|action| format!("{:?}", action) == name)
+}
+
+/// The keys bound to every action. The keys are the `KeyboardEvent.code` values, they name the
+/// physical keys, so the default WASD keys are ZQSD on an AZERTY keyboard.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub keys : BTreeMap<Action, Vec<String>>
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        let defaults : [(Action, &[&str]); 5] = [
+            (Action::MoveLeft, &["ArrowLeft", "KeyA"]),
+            (Action::MoveRight, &["ArrowRight", "KeyD"]),
+            (Action::Launch, &["Space", "KeyW"]),
+            (Action::Pause, &["Escape", "KeyP"]),
+            (Action::Confirm, &["Enter", "NumpadEnter"])
+        ];
+
+        KeyBindings {
+            keys: defaults.iter()
+                .map(|(action, keys)| (*action, keys.iter().map(|key| key.to_string()).collect()))
+                .collect()
+        }
+    }
+}
+
+impl KeyBindings {
+    /// The actions bound to the key, at most one unless the bindings were edited by hand.
+    pub fn actions(&self, code : &str) -> Vec<Action> {
+        self.keys.iter()
+            .filter(|(_, keys)| keys.iter().any(|key| key == code))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    pub fn keys(&self, action : Action) -> &[String] {
+        self.keys.get(&action).map_or(&[], |keys| keys.as_slice())
+    }
+
+    /// Adds the key to the action, the key is taken away from the other actions.
+    pub fn bind(&mut self, action : Action, code : &str) {
+        for keys in self.keys.values_mut() {
+            keys.retain(|key| key != code);
+        }
+
+        self.keys.entry(action).or_default().push(code.to_owned());
+    }
+
+    pub fn clear(&mut self, action : Action) {
+        self.keys.remove(&action);
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        return Ok(serde_json::to_string(self)?);
+    }
+
+    /// The actions missing from the JSON keep their default keys.
+    pub fn from_json(json : &str) -> anyhow::Result<KeyBindings> {
+        let loaded : KeyBindings = serde_json::from_str(json)?;
+        let mut bindings = KeyBindings::default();
+        bindings.keys.extend(loaded.keys);
+        return Ok(bindings);
+    }
+}
+
+/// A short label of the key code, e.g. `A` for `KeyA`.
+pub fn key_label(code : &str) -> String {
+    let label = code.trim_start_matches("Key").trim_start_matches("Digit");
+
+    return match label {
+        "ArrowLeft" => "←".to_owned(),
+        "ArrowRight" => "→".to_owned(),
+        "ArrowUp" => "↑".to_owned(),
+        "ArrowDown" => "↓".to_owned(),
+        "Escape" => "Esc".to_owned(),
+        _ => label.to_owned()
+    };
+}
+
+pub fn load_key_bindings() -> anyhow::Result<KeyBindings> {
+    return match local_storage()?.get_item(KEY_BINDINGS_KEY).to_anyhow()? {
+        Some(json) => KeyBindings::from_json(json.as_str()),
+        None => Ok(KeyBindings::default())
+    };
+}
+
+pub fn save_key_bindings(bindings : &KeyBindings) -> anyhow::Result<()> {
+    local_storage()?.set_item(KEY_BINDINGS_KEY, bindings.to_json()?.as_str()).to_anyhow()?;
+    return Ok(());
+}
+
+const TOUCH_SCHEME_KEY : &'static str = "touch-controls";
+
+/// How the touches move the bat.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TouchScheme {
+    /// The bat follows the finger.
+    Drag,
+    /// The bat moves as much as the finger, wherever the finger is put down.
+    RelativeDrag,
+    /// Holding the left or the right half of the screen moves the bat that way.
+    Buttons
+}
+
+/// The schemes in the order they are cycled through, with their names and their keys in the
+/// local storage.
+pub const TOUCH_SCHEMES : [(TouchScheme, &'static str, &'static str); 3] = [
+    (TouchScheme::Drag, "Drag", "drag"),
+    (TouchScheme::RelativeDrag, "Relative drag", "relative"),
+    (TouchScheme::Buttons, "Buttons", "buttons")
+];
+
+impl TouchScheme {
+    pub fn name(&self) -> &'static str {
+        TOUCH_SCHEMES.iter().find(|(scheme, _, _)| scheme == self).map_or("", |(_, name, _)| name)
+    }
+
+    pub fn next(&self) -> TouchScheme {
+        let index = TOUCH_SCHEMES.iter().position(|(scheme, _, _)| scheme == self).unwrap_or(0);
+        return TOUCH_SCHEMES[(index + 1) % TOUCH_SCHEMES.len()].0;
+    }
+}
+
+pub fn load_touch_scheme() -> anyhow::Result<TouchScheme> {
+    let value = local_storage()?.get_item(TOUCH_SCHEME_KEY).to_anyhow()?;
+
+    return Ok(TOUCH_SCHEMES.iter()
+        .find(|(_, _, key)| Some(*key) == value.as_deref())
+        .map_or(TouchScheme::Drag, |(scheme, _, _)| *scheme));
+}
+
+pub fn save_touch_scheme(touch_scheme : TouchScheme) -> anyhow::Result<()> {
+    let key = TOUCH_SCHEMES.iter().find(|(scheme, _, _)| *scheme == touch_scheme).map_or("drag", |(_, _, key)| key);
+    local_storage()?.set_item(TOUCH_SCHEME_KEY, key).to_anyhow()?;
+    return Ok(());
+}
+
+/// Turns the finger position into the target x of the bat for the drag schemes.
+#[derive(Default)]
+pub struct TouchDrag {
+    /// The identifier of the touch, and the x of the finger and the bat when it started.
+    anchor : Option<(i32, f32, f32)>
+}
+
+impl TouchDrag {
+    pub fn new() -> TouchDrag {
+        TouchDrag { anchor: None }
+    }
+
+    /// The `touch` is the identifier and the x in the game coordinates of the finger, `None`
+    /// when the screen isn't touched. Returns `None` if the bat shouldn't be steered.
+    pub fn target(&mut self, scheme : TouchScheme, touch : Option<(i32, f32)>, bat_x : f32) -> Option<f32> {
+        let (identifier, x) = match touch {
+            Some(touch) => touch,
+            None => {
+                self.anchor = None;
+                return None;
+            }
+        };
+
+        match scheme {
+            TouchScheme::Drag => Some(x),
+            TouchScheme::RelativeDrag => {
+                let (_, start_x, start_bat_x) = match self.anchor {
+                    Some(anchor) if anchor.0 == identifier => anchor,
+                    _ => {
+                        self.anchor = Some((identifier, x, bat_x));
+                        (identifier, x, bat_x)
+                    }
+                };
+
+                Some(start_bat_x + x - start_x)
+            },
+            TouchScheme::Buttons => None
+        }
+    }
+}
+
+/// The part of the stick travel around the center that is ignored, the sticks rarely rest at zero.
+pub const GAMEPAD_DEAD_ZONE : f32 = 0.2f32;
+
+/// The actions of the buttons in the standard gamepad layout, A, X, Start and the D-pad.
+pub const GAMEPAD_BUTTONS : [(u32, Action); 5] = [
+    (0, Action::Confirm),
+    (2, Action::Launch),
+    (9, Action::Pause),
+    (14, Action::MoveLeft),
+    (15, Action::MoveRight)
+];
+
+pub fn gamepad_action(button : u32) -> Option<Action> {
+    GAMEPAD_BUTTONS.iter().find(|(b, _)| *b == button).map(|(_, action)| *action)
+}
+
+/// Zeroes the stick inside the dead zone and rescales the rest, so the bat can still move
+/// slowly just outside of it.
+pub fn apply_dead_zone(value : f32, dead_zone : f32) -> f32 {
+    let magnitude = value.abs().min(1f32);
+
+    if magnitude <= dead_zone {
+        return 0f32;
+    }
+
+    return value.signum() * (magnitude - dead_zone) / (1f32 - dead_zone);
+}
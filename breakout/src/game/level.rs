@@ -0,0 +1,50 @@
+use crate::utils::*;
+use crate::executor::*;
+use crate::game::GameState;
+use gamelib::Simulation;
+use gamelib::level::*;
+use gamelib::replay::*;
+use std::{rc::Rc, rc::Weak, cell::RefCell};
+
+/// Restarts the game with the level.
+pub fn start_level(game_state : &mut GameState, level : Level) -> anyhow::Result<()> {
+    let seed = game_state.simulation.seed;
+    let mode = game_state.simulation.mode;
+    let config = game_state.simulation.config.clone();
+
+    game_state.simulation = Simulation::with_config(seed, mode, Some(level.clone()), config.clone())?;
+    game_state.replay = Replay::with_config(seed, mode, Some(level), config);
+
+    return Ok(());
+}
+
+pub async fn load_level_inner(game_state : Weak<RefCell<GameState>>, uri : String) -> anyhow::Result<()> {
+    let level = crate::webapi::fetch_level(uri).await?;
+    level.validate()?;
+
+    if let Some(game_state) = game_state.upgrade() {
+        start_level(&mut game_state.borrow_mut(), level)?;
+    }
+
+    return Ok(());
+}
+
+pub async fn load_level_async(game_state : Weak<RefCell<GameState>>, uri : String) {
+    match load_level_inner(game_state, uri).await {
+        Err(error) => log!("Failed to load level: {:?}", error),
+        Ok(_) => ()
+    }
+}
+
+/// Starts the level from the `level` query parameter, if there is one. The parameter is either
+/// the name of a classic level or an URI of a level file.
+pub fn load_level(game_state : &Rc<RefCell<GameState>>) -> anyhow::Result<()> {
+    if let Some(name) = query_parameter("level")? {
+        match classic_level(name.as_str())? {
+            Some(level) => start_level(&mut game_state.borrow_mut(), level)?,
+            None => execute(load_level_async(Rc::downgrade(game_state), name))
+        }
+    }
+
+    return Ok(());
+}
@@ -1,19 +1,34 @@
-mod bat;
+pub mod bat;
 mod ball;
 mod bricks;
-mod config;
+mod bonus;
 pub mod utils;
 mod scoreboard;
+mod replay;
+mod level;
+mod audio;
+pub mod particles;
+pub mod input;
 
 use glm::*;
 use crate::event::*;
+use crate::executor::*;
 use crate::utils::*;
 use crate::game::bricks::*;
 use crate::game::bat::*;
 use crate::game::ball::*;
-use crate::game::utils::*;
+use crate::game::bonus::*;
 use crate::game::scoreboard::*;
-use std::cmp::{max};
+use crate::game::replay::*;
+use crate::game::level::*;
+use crate::game::particles::*;
+use crate::game::input::*;
+use crate::renderer::*;
+pub use crate::game::audio::AudioPlayer;
+use gamelib::config;
+use gamelib::config::GameConfig;
+use gamelib::{GameMode, GameStage, Input, Simulation};
+use gamelib::replay::{Replay, ReplayPlayer};
 use std::include_str;
 use std::rc::Rc;
 use std::cell::RefCell;
@@ -24,77 +39,85 @@ use web_sys::*;
 
 #[allow(dead_code)]
 pub fn draw_vector(
-    rendering_context : &CanvasRenderingContext2d,
+    renderer : &mut dyn Renderer,
     origin : Vec2,
     target : Vec2,
     color : &'static str) -> anyhow::Result<()> {
-    rendering_context.begin_path();
-    rendering_context.set_line_width(2f64);
-    rendering_context.move_to(origin.x as f64, origin.y as f64);
-    rendering_context.line_to(target.x as f64, target.y as f64);
-    rendering_context.set_stroke_style(&JsValue::from_str(color));
-    rendering_context.stroke();
+    renderer.line(origin, target, 2f32, color)?;
     return Ok(());
 }
 
-fn decrease_lives(game_state : &mut GameState, game_time : GameTime) {
-    let ball : &mut Ball = &mut game_state.ball;
-
-    game_state.lives = max(game_state.lives, 1) - 1;
-
-    if game_state.lives != 0 {
-        ball.reset_position();
-    }
-    else {
-        game_state.stage = GameStage::GameOver;
-        game_state.game_over_time = game_time.real_time;
-    }
-}
-
-pub enum GameStage {
-    Gameplay,
-    GameOver,
-    ScoreBoard
-}
+/// The number of seconds counted down before a paused game is resumed.
+const RESUME_COUNTDOWN : f64 = 3f64;
 
 pub struct GameState {
-    pub stage : GameStage,
-    pub bat : Bat,
-    pub ball : Ball,
-    pub bricks : Bricks,
+    pub simulation : Simulation,
+    pub replay : Replay,
+    pub playback : Option<ReplayPlayer>,
+    /// The session the score is submitted with, the seed of the game comes from it. `None` until
+    /// the server issues it, and for the games that started before that.
+    pub session_id : Option<String>,
     pub last_time : f64,
-    pub time : GameTime,
-    pub score : i64,
     pub score_id : Rc<RefCell<uuid::Uuid>>,
-    pub lives : u32,
-    pub game_over_time : f64,
     pub keyboard_state : Rc<RefCell<KeyboardState>>,
     pub touch_tracker : Rc<RefCell<TouchTracker>>,
+    pub pointer_tracker : Rc<RefCell<PointerTracker>>,
+    pub gamepad : GamepadState,
     pub reset_requested : bool,
-    submit_emitter : ClosureHandle
+    pub pause_requested : bool,
+    pub resume_requested : bool,
+    /// The end of the resume countdown, `None` when the countdown isn't running.
+    pub resume_time : Option<f64>,
+    /// Survives the restarts of the game, the browsers limit the number of audio contexts.
+    pub audio : Rc<RefCell<AudioPlayer>>,
+    pub particles : Particles,
+    /// Draw the trails of the balls, off for the users who prefer reduced motion.
+    pub ball_trail : bool,
+    pub key_bindings : KeyBindings,
+    /// The action waiting for a key to be bound to it from the controls menu.
+    pub rebinding : Option<Action>,
+    pub touch_scheme : TouchScheme,
+    touch_drag : TouchDrag,
+    /// The listeners of the page events, removed when the game is restarted.
+    listeners : Vec<ClosureHandle>,
+    submit_emitter : ClosureHandle,
+    pause_emitter : ClosureHandle,
+    pause_menu_emitter : ClosureHandle,
+    mute_emitter : ClosureHandle
 }
 
 impl GameState {
     pub fn new(
-        bat : Bat,
-        ball : Ball,
-        bricks : Bricks,
-        last_time : f64) -> Rc<RefCell<GameState>> {
+        simulation : Simulation,
+        last_time : f64,
+        audio : Rc<RefCell<AudioPlayer>>) -> Rc<RefCell<GameState>> {
         let game_state = GameState {
-            stage: GameStage::Gameplay,
-            bat: bat,
-            ball: ball,
-            bricks: bricks,
+            replay: Replay::with_config(simulation.seed, simulation.mode, simulation.level.clone(), simulation.config.clone()),
+            playback: None,
+            session_id: None,
+            particles: Particles::new(simulation.seed, particle_budget()),
+            ball_trail: ball_trail_enabled().unwrap_or(true),
+            key_bindings: load_key_bindings().unwrap_or_default(),
+            rebinding: None,
+            touch_scheme: load_touch_scheme().unwrap_or(TouchScheme::Drag),
+            touch_drag: TouchDrag::new(),
+            simulation: simulation,
             last_time: last_time,
-            time: GameTime { sim_time: 0f64, real_time: 0f64, elapsed: 0f32 },
-            score: 0,
             score_id: Rc::new(RefCell::new(uuid::Uuid::nil())),
-            lives: 3,
-            game_over_time: 0f64,
             keyboard_state: KeyboardState::new(),
             touch_tracker: TouchTracker::new(),
+            pointer_tracker: PointerTracker::new(),
+            gamepad: GamepadState::new(),
             reset_requested: false,
-            submit_emitter: ClosureHandle::Empty
+            pause_requested: false,
+            resume_requested: false,
+            resume_time: None,
+            audio: audio,
+            listeners: Vec::new(),
+            submit_emitter: ClosureHandle::Empty,
+            pause_emitter: ClosureHandle::Empty,
+            pause_menu_emitter: ClosureHandle::Empty,
+            mute_emitter: ClosureHandle::Empty
         };
 
         let game_state = Rc::new(RefCell::new(game_state));
@@ -106,24 +129,70 @@ impl GameState {
         let window = web_sys::window().unwrap();
         let document = window.document().unwrap();
 
-        let on_keyup : Box<dyn FnMut(web_sys::KeyboardEvent)> = {
-            let game_state = game_state.clone();
+        // The listeners are owned by the game state, so they hold only weak references to it.
+        let on_keydown : Box<dyn FnMut(web_sys::KeyboardEvent)> = {
+            let game_state = std::rc::Rc::downgrade(&game_state);
 
             {
                 Box::new(move |event : web_sys::KeyboardEvent| {
+                    let game_state = match game_state.upgrade() {
+                        Some(game_state) => game_state,
+                        None => return
+                    };
+
                     let event = event.dyn_into::<web_sys::KeyboardEvent>();
 
                     match event {
                         Ok(event) => {
-                            match event.key().as_str() {
-                                "Enter" => {
-                                    match game_state.borrow().stage {
-                                        GameStage::ScoreBoard => {
-                                            Self::submit_score(game_state.clone())
-                                        },
-                                        _ => {}
-                                    }
+                            game_state.borrow().audio.borrow().unlock();
+
+                            let code = key_code(&event);
+
+                            if game_state.borrow_mut().rebind_key(code.as_str()) {
+                                event.prevent_default();
+                                return;
+                            }
+
+                            if event.repeat() {
+                                return;
+                            }
+
+                            let actions = game_state.borrow().key_bindings.actions(code.as_str());
+
+                            // Only Enter counts while the player types the nickname.
+                            let typing = event.target()
+                                .and_then(|target| target.dyn_into::<Element>().ok())
+                                .map_or(false, |target| target.tag_name() == "INPUT");
+
+                            if typing {
+                                if actions.contains(&Action::Confirm) {
+                                    GameState::trigger(&game_state, Action::Confirm);
+                                }
+
+                                return;
+                            }
+
+                            if !actions.is_empty() {
+                                for action in actions {
+                                    GameState::trigger(&game_state, action);
                                 }
+
+                                return;
+                            }
+
+                            match event.key().as_str() {
+                                "t" | "T" => {
+                                    game_state.borrow_mut().toggle_ball_trail();
+                                },
+                                "m" | "M" => {
+                                    game_state.borrow().audio.borrow_mut().toggle_mute();
+                                },
+                                "-" => {
+                                    game_state.borrow().audio.borrow_mut().change_volume(-1);
+                                },
+                                "=" | "+" => {
+                                    game_state.borrow().audio.borrow_mut().change_volume(1);
+                                },
                                 _ => {}
                             }
                         },
@@ -133,10 +202,127 @@ impl GameState {
             }
         };
 
-        let closure = Closure::wrap(on_keyup);
-        document.add_event_listener_with_callback("keyup", closure.as_ref()
-            .unchecked_ref()).unwrap();
-        closure.forget();
+        let mut closure = ClosureHandle::new(on_keydown);
+        closure.listen(&document, "keydown").unwrap();
+        game_state.borrow_mut().listeners.push(closure);
+
+        // The taps and clicks count as the interaction that allows the audio to start.
+        let on_interaction : Box<dyn FnMut(JsValue)> = {
+            let game_state = std::rc::Rc::downgrade(&game_state);
+
+            Box::new(move |_ : JsValue| {
+                if let Some(game_state) = game_state.upgrade() {
+                    game_state.borrow().audio.borrow().unlock();
+                }
+            })
+        };
+
+        let mut closure = ClosureHandle::new(on_interaction);
+        closure.listen(&document, "click").unwrap();
+        closure.listen(&document, "touchend").unwrap();
+        game_state.borrow_mut().listeners.push(closure);
+
+        // A click on the game while playing locks the mouse, so it can't leave the game area.
+        let on_pointer_down : Box<dyn FnMut(web_sys::PointerEvent)> = {
+            let game_state = std::rc::Rc::downgrade(&game_state);
+
+            Box::new(move |event : web_sys::PointerEvent| {
+                let target = event.target().and_then(|target| target.dyn_into::<Element>().ok());
+
+                if event.pointer_type() != "mouse" || target.as_ref().map(|target| target.id()).as_deref() != Some("main-overlay-id") {
+                    return;
+                }
+
+                if let Some(game_state) = game_state.upgrade() {
+                    if game_state.borrow().simulation.stage == GameStage::Gameplay {
+                        target.unwrap().request_pointer_lock();
+                    }
+                }
+            })
+        };
+
+        let overlay : HtmlElement = document.get_element_by_id("main-overlay-id").unwrap().unchecked_into();
+        let mut closure = ClosureHandle::new(on_pointer_down);
+        closure.listen(&overlay, "pointerdown").unwrap();
+        game_state.borrow_mut().listeners.push(closure);
+
+        // The browsers release the lock on Escape, the game is paused then like with the keys.
+        let on_pointer_lock_change : Box<dyn FnMut(JsValue)> = {
+            let game_state = std::rc::Rc::downgrade(&game_state);
+
+            Box::new(move |_ : JsValue| {
+                let locked = web_sys::window().unwrap().document().unwrap().pointer_lock_element().is_some();
+
+                if let Some(game_state) = game_state.upgrade() {
+                    if !locked && game_state.borrow().simulation.stage == GameStage::Gameplay {
+                        game_state.borrow_mut().pause_requested = true;
+                    }
+                }
+            })
+        };
+
+        let mut closure = ClosureHandle::new(on_pointer_lock_change);
+        closure.listen(&document, "pointerlockchange").unwrap();
+        game_state.borrow_mut().listeners.push(closure);
+
+        let on_focus_lost : Box<dyn FnMut(JsValue)> = {
+            let game_state = std::rc::Rc::downgrade(&game_state);
+
+            Box::new(move |_ : JsValue| {
+                let hidden = web_sys::window().unwrap().document().unwrap().hidden();
+                let focused = web_sys::window().unwrap().document().unwrap().has_focus().unwrap_or(true);
+
+                if let Some(game_state) = game_state.upgrade() {
+                    if hidden || !focused {
+                        game_state.borrow_mut().pause_requested = true;
+                    }
+                }
+            })
+        };
+
+        let mut closure = ClosureHandle::new(on_focus_lost);
+        closure.listen(&document, "visibilitychange").unwrap();
+        closure.listen(&window, "blur").unwrap();
+        game_state.borrow_mut().listeners.push(closure);
+
+        game_state.borrow_mut().pause_emitter = ClosureHandle::new({
+            let game_state = std::rc::Rc::downgrade(&game_state);
+
+            Box::new(move |_ : JsValue| {
+                if let Some(game_state) = game_state.upgrade() {
+                    GameState::trigger(&game_state, Action::Pause);
+                }
+            })
+        });
+
+        // The buttons of the controls menu have the `data-action` attribute, a click anywhere
+        // else in the pause menu resumes the game.
+        game_state.borrow_mut().pause_menu_emitter = ClosureHandle::new({
+            let game_state = std::rc::Rc::downgrade(&game_state);
+
+            Box::new(move |event : web_sys::Event| {
+                if let Some(game_state) = game_state.upgrade() {
+                    let target = event.target().and_then(|target| target.dyn_into::<Element>().ok());
+
+                    match target.and_then(|target| target.get_attribute("data-action")).as_deref() {
+                        Some("reset") => game_state.borrow_mut().reset_key_bindings(),
+                        Some("touch-scheme") => game_state.borrow_mut().cycle_touch_scheme(),
+                        Some(name) => game_state.borrow_mut().rebinding = parse_action(name),
+                        None => GameState::trigger(&game_state, Action::Launch)
+                    }
+                }
+            })
+        });
+
+        game_state.borrow_mut().mute_emitter = ClosureHandle::new({
+            let game_state = std::rc::Rc::downgrade(&game_state);
+
+            Box::new(move |_ : JsValue| {
+                if let Some(game_state) = game_state.upgrade() {
+                    game_state.borrow().audio.borrow_mut().toggle_mute();
+                }
+            })
+        });
 
         game_state.borrow_mut().submit_emitter = ClosureHandle::new({
             let game_state = std::rc::Rc::downgrade(&game_state);
@@ -148,6 +334,115 @@ impl GameState {
         });
     }
 
+    /// Does what the action asks for in the current stage of the game. The movement actions are
+    /// held rather than triggered, they are read in `update`.
+    pub fn trigger(game_state : &Rc<RefCell<GameState>>, action : Action) {
+        let stage = game_state.borrow().simulation.stage;
+
+        match (action, stage) {
+            (Action::Pause, _) => game_state.borrow_mut().toggle_pause(),
+            (Action::Launch, GameStage::Paused) | (Action::Confirm, GameStage::Paused) => {
+                game_state.borrow_mut().resume_requested = true;
+            },
+            (Action::Confirm, GameStage::ScoreBoard) => GameState::submit_score(game_state.clone()),
+            _ => {}
+        }
+    }
+
+    /// Whether a key bound to the action is down or a touch asks for it.
+    pub fn is_action_held(&self, action : Action) -> bool {
+        let keyboard_state = self.keyboard_state.borrow();
+
+        if self.key_bindings.keys(action).iter().any(|key| keyboard_state.is_down(key)) {
+            return true;
+        }
+
+        if self.gamepad.buttons.iter().any(|button| gamepad_action(*button) == Some(action)) {
+            return true;
+        }
+
+        if self.touch_scheme != TouchScheme::Buttons {
+            return false;
+        }
+
+        let half = self.simulation.config.arena_width * 0.5f32;
+
+        return self.touch_positions().iter().any(|(_, position)| match action {
+            Action::MoveLeft => position.x < half,
+            Action::MoveRight => position.x >= half,
+            _ => false
+        });
+    }
+
+    /// The identifiers of the touches and their positions in the game coordinates.
+    fn touch_positions(&self) -> Vec<(i32, Vec2)> {
+        let geometry = match CanvasGeometry::get() {
+            Some(geometry) => geometry,
+            None => return Vec::new()
+        };
+
+        let game_area = vec2(self.simulation.config.arena_width, self.simulation.config.arena_height);
+
+        return self.touch_tracker.borrow().touches.iter().map(|touch| {
+            let position = geometry.to_canvas(vec2(touch.client_x as f32, touch.client_y as f32));
+            (touch.identifier, canvas_to_game(position, game_area, geometry.canvas_size))
+        }).collect();
+    }
+
+    fn cycle_touch_scheme(&mut self) {
+        self.touch_scheme = self.touch_scheme.next();
+
+        if let Err(error) = save_touch_scheme(self.touch_scheme) {
+            log!("Failed to save the touch controls: {:?}", error);
+        }
+    }
+
+    /// Binds the key to the action picked in the controls menu, Escape cancels and Backspace
+    /// or Delete unbinds the action. Returns `false` if no action was waiting for a key.
+    fn rebind_key(&mut self, code : &str) -> bool {
+        let action = match self.rebinding.take() {
+            Some(action) => action,
+            None => return false
+        };
+
+        match code {
+            "Escape" => return true,
+            "Backspace" | "Delete" => self.key_bindings.clear(action),
+            _ => self.key_bindings.bind(action, code)
+        }
+
+        if let Err(error) = save_key_bindings(&self.key_bindings) {
+            log!("Failed to save the key bindings: {:?}", error);
+        }
+
+        return true;
+    }
+
+    fn reset_key_bindings(&mut self) {
+        self.key_bindings = KeyBindings::default();
+        self.rebinding = None;
+
+        if let Err(error) = save_key_bindings(&self.key_bindings) {
+            log!("Failed to save the key bindings: {:?}", error);
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        match self.simulation.stage {
+            GameStage::Gameplay => self.pause_requested = true,
+            GameStage::Paused => self.resume_requested = true,
+            _ => {}
+        }
+    }
+
+    fn toggle_ball_trail(&mut self) {
+        self.ball_trail = !self.ball_trail;
+
+        if let Err(error) = save_ball_trail_enabled(self.ball_trail) {
+            log!("Failed to save the ball trail setting: {:?}", error);
+        }
+    }
+
     fn submit_score(game_state : std::rc::Rc<std::cell::RefCell<GameState>>) {
         let window = web_sys::window().unwrap();
         let document = window.document().unwrap();
@@ -168,19 +463,57 @@ impl GameState {
         }
     }
 
-    pub fn init(time : f64) -> Rc<RefCell<GameState>> {
-        let bat = Bat::new();
-        let mut ball = Ball::new();
+    pub fn init(time : f64, audio : Rc<RefCell<AudioPlayer>>) -> Rc<RefCell<GameState>> {
+        let seed = generate_seed().unwrap();
+
+        log!("game seed: {}", seed);
+
+        let mode = match query_parameter("mode").unwrap().as_deref() {
+            Some("chaos") => GameMode::Chaos,
+            _ => GameMode::Classic
+        };
+
+        let simulation = Simulation::with_config(seed, mode, None, load_game_config()).unwrap_or_else(|error| {
+            log!("Failed to start the configured game: {:?}", error);
+            Simulation::with_mode(seed, mode)
+        });
+        let game_state = GameState::new(simulation, time, audio);
+
+        load_level(&game_state).unwrap();
+        load_replay(&game_state).unwrap();
+        execute(request_session_async(Rc::downgrade(&game_state)));
+
+        return game_state;
+    }
+}
 
-        ball.reset_position();
+/// The rules of the game from the `config` query parameter with a JSON object, then the single
+/// fields from the query parameters of the same names, e.g. `?num_lives=5&ball_velocity=600`.
+/// The invalid values are logged and ignored.
+fn load_game_config() -> GameConfig {
+    let mut config = GameConfig::default();
 
-        let bricks = Bricks::new();
+    match query_parameter("config") {
+        Ok(Some(json)) => match GameConfig::from_json(json.as_str()) {
+            Ok(loaded) => config = loaded,
+            Err(error) => log!("Invalid game config: {:?}", error)
+        },
+        _ => ()
+    }
 
-        return GameState::new(bat, ball, bricks, time);
+    for name in GameConfig::field_names() {
+        if let Ok(Some(value)) = query_parameter(name.as_str()) {
+            match config.with_field(name.as_str(), value.as_str()) {
+                Ok(changed) => config = changed,
+                Err(error) => log!("Invalid game config field '{}': {:?}", name, error)
+            }
+        }
     }
+
+    return config;
 }
 
-pub fn init_overlay(_game_state : &mut GameState, _time : f64) -> anyhow::Result<()> {
+pub fn init_overlay(game_state : &mut GameState, _time : f64) -> anyhow::Result<()> {
     let window = web_sys::window().unwrap();
     let document = window.document().unwrap();
     let overlay : HtmlElement = document.get_element_by_id("main-overlay-id")
@@ -198,6 +531,36 @@ pub fn init_overlay(_game_state : &mut GameState, _time : f64) -> anyhow::Result
     lives.set_inner_html("");
     overlay.append_child(&lives).to_anyhow()?;
 
+    let speed : HtmlElement = document.create_element("span").unwrap().unchecked_into();
+    speed.set_id("footer-speed");
+    speed.set_inner_html("");
+    overlay.append_child(&speed).to_anyhow()?;
+
+    let pause_button : HtmlElement = document.create_element("span").unwrap().unchecked_into();
+    pause_button.set_id("pause-button");
+    pause_button.set_inner_html("⏸");
+    overlay.append_child(&pause_button).to_anyhow()?;
+
+    let mute_button : HtmlElement = document.create_element("span").unwrap().unchecked_into();
+    mute_button.set_id("mute-button");
+    overlay.append_child(&mute_button).to_anyhow()?;
+
+    bind_overlay(game_state)?;
+
+    return Ok(());
+}
+
+/// Connects the buttons of the overlay to the game state, the overlay outlives the restarts of
+/// the game.
+pub fn bind_overlay(game_state : &GameState) -> anyhow::Result<()> {
+    let document = web_sys::window().unwrap().document().unwrap();
+
+    let pause_button : HtmlElement = document.get_element_by_id("pause-button").unwrap().unchecked_into();
+    pause_button.set_onclick(Some(game_state.pause_emitter.function()));
+
+    let mute_button : HtmlElement = document.get_element_by_id("mute-button").unwrap().unchecked_into();
+    mute_button.set_onclick(Some(game_state.mute_emitter.function()));
+
     return Ok(());
 }
 
@@ -212,7 +575,7 @@ pub fn update_game_over(
 
     match game_over {
         Some(element) => {
-            match game_state.stage {
+            match game_state.simulation.stage {
                 GameStage::GameOver => {},
                 _ => {
                     overlay.remove_child(&element).to_anyhow()?;
@@ -220,11 +583,12 @@ pub fn update_game_over(
             }
         },
         None => {
-            match game_state.stage {
+            match game_state.simulation.stage {
                 GameStage::GameOver => {
                     let game_over : HtmlElement = document.create_element("div").unwrap().unchecked_into();
                     game_over.set_id(game_over_id);
-                    game_over.set_inner_html("<span>Game Over</span>");
+                    let cleared = game_state.simulation.level.is_some() && game_state.simulation.bricks.cleared();
+                    game_over.set_inner_html(if cleared { "<span>Level Cleared</span>" } else { "<span>Game Over</span>" });
                     overlay.append_child(&game_over).to_anyhow()?;
                 },
                 _ => {}
@@ -235,6 +599,106 @@ pub fn update_game_over(
     return Ok(());
 }
 
+fn resume_hint(key_bindings : &KeyBindings) -> String {
+    match key_bindings.keys(Action::Launch).first() {
+        Some(key) => format!("Press {}", key_label(key)),
+        None => "Click".to_owned()
+    }
+}
+
+/// The buttons that rebind the keys of the actions.
+fn controls_menu(key_bindings : &KeyBindings, rebinding : Option<Action>, touch_scheme : TouchScheme) -> String {
+    let mut menu = String::from("<div id=\"controls-menu\">");
+
+    for (action, name) in ACTIONS.iter() {
+        let keys = if rebinding == Some(*action) {
+            "press a key…".to_owned()
+        }
+        else {
+            key_bindings.keys(*action).iter().map(|key| key_label(key)).collect::<Vec<String>>().join(", ")
+        };
+
+        menu.push_str(format!("<button data-action=\"{:?}\">{}: {}</button>", action, name, keys).as_str());
+    }
+
+    menu.push_str(format!("<button data-action=\"touch-scheme\">Touch: {}</button>", touch_scheme.name()).as_str());
+    menu.push_str("<button data-action=\"reset\">Reset controls</button></div>");
+
+    return menu;
+}
+
+/// Shows the arrows of the on-screen buttons, they only hint where to touch and let the touches
+/// through to the overlay.
+pub fn update_touch_buttons(
+    game_state : &GameState,
+    overlay : &HtmlElement) -> anyhow::Result<()> {
+
+    let document = overlay.owner_document().unwrap();
+
+    let visible = game_state.touch_scheme == TouchScheme::Buttons && match game_state.simulation.stage {
+        GameStage::Gameplay | GameStage::Paused => true,
+        _ => false
+    };
+
+    for (id, arrow) in [("touch-left", "◀"), ("touch-right", "▶")].iter() {
+        match (document.get_element_by_id(id), visible) {
+            (Some(element), false) => {
+                overlay.remove_child(&element).to_anyhow()?;
+            },
+            (None, true) => {
+                let button : HtmlElement = document.create_element("div").unwrap().unchecked_into();
+                button.set_id(id);
+                button.set_class_name("touch-button");
+                button.set_inner_html(arrow);
+                overlay.append_child(&button).to_anyhow()?;
+            },
+            _ => {}
+        }
+    }
+
+    return Ok(());
+}
+
+pub fn update_pause_menu(
+    game_state : &mut GameState,
+    overlay : &HtmlElement,
+    time : f64) -> anyhow::Result<()> {
+
+    let document = overlay.owner_document().unwrap();
+
+    let pause_menu_id = "pause-menu";
+    let pause_menu = document.get_element_by_id(pause_menu_id);
+
+    match (pause_menu, game_state.simulation.stage) {
+        (Some(element), GameStage::Paused) => {
+            let content = match game_state.resume_time {
+                Some(resume_time) => format!("<span>{}</span>", (resume_time - time).ceil().max(1f64)),
+                None => format!(
+                    "<span>Paused<br/><small>{} or tap to resume</small><br/>{}</span>",
+                    resume_hint(&game_state.key_bindings),
+                    controls_menu(&game_state.key_bindings, game_state.rebinding, game_state.touch_scheme))
+            };
+
+            if element.inner_html() != content {
+                element.set_inner_html(&content);
+            }
+        },
+        (Some(element), _) => {
+            game_state.rebinding = None;
+            overlay.remove_child(&element).to_anyhow()?;
+        },
+        (None, GameStage::Paused) => {
+            let pause_menu : HtmlElement = document.create_element("div").unwrap().unchecked_into();
+            pause_menu.set_id(pause_menu_id);
+            pause_menu.add_event_listener_with_callback("click", game_state.pause_menu_emitter.function()).to_anyhow()?;
+            overlay.append_child(&pause_menu).to_anyhow()?;
+        },
+        (None, _) => {}
+    };
+
+    return Ok(());
+}
+
 pub fn update_score_board(
     game_state : &Rc<RefCell<GameState>>,
     overlay : &HtmlElement) -> anyhow::Result<()> {
@@ -246,7 +710,7 @@ pub fn update_score_board(
 
     match score_board {
         Some(element) => {
-            match game_state.borrow().stage {
+            match game_state.borrow().simulation.stage {
                 GameStage::ScoreBoard => {},
                 _ => {
                     overlay.remove_child(&element).to_anyhow()?;
@@ -254,11 +718,19 @@ pub fn update_score_board(
             }
         },
         None => {
-            match game_state.borrow().stage {
+            match game_state.borrow().simulation.stage {
                 GameStage::ScoreBoard => {
+                    // The replays watched from the links aren't submitted again.
+                    let session_id = match game_state.borrow().playback {
+                        Some(_) => None,
+                        None => game_state.borrow().session_id.clone()
+                    };
+
                     create_scoreboard(
                         game_state.borrow().submit_emitter.function().clone(),
-                        game_state.borrow().score,
+                        game_state.borrow().simulation.score,
+                        game_state.borrow().replay.clone(),
+                        session_id,
                         game_state.borrow().score_id.clone(),
                         score_board_id)?;
                 },
@@ -272,7 +744,7 @@ pub fn update_score_board(
 
 pub fn update_overlay(
     game_state : &Rc<RefCell<GameState>>,
-    _time : f64) -> anyhow::Result<()> {
+    time : f64) -> anyhow::Result<()> {
     let window = web_sys::window().unwrap();
     let document = window.document().unwrap();
     let overlay : HtmlElement = document.get_element_by_id("main-overlay-id")
@@ -280,140 +752,254 @@ pub fn update_overlay(
 
     let score : HtmlElement = document.get_element_by_id("footer-score").unwrap().unchecked_into();
     let lives : HtmlElement = document.get_element_by_id("footer-lives").unwrap().unchecked_into();
+    let speed : HtmlElement = document.get_element_by_id("footer-speed").unwrap().unchecked_into();
 
-    match game_state.borrow().stage {
-        GameStage::Gameplay | GameStage::GameOver => {
-            let score_str = game_state.borrow().score.to_string();
+    match game_state.borrow().simulation.stage {
+        GameStage::Gameplay | GameStage::Paused | GameStage::GameOver => {
+            let score_str = game_state.borrow().simulation.score.to_string();
             score.style().remove_property("display").to_anyhow()?;
             score.set_inner_html(&score_str[..]);
 
-            let lives_str =  "❤".repeat(game_state.borrow().lives as usize);
+            let lives_str =  "❤".repeat(game_state.borrow().simulation.lives as usize);
             lives.style().remove_property("display").to_anyhow()?;
             lives.set_inner_html(&lives_str[..]);
+
+            let speed_scale = game_state.borrow().simulation.difficulty().ball_speed_scale;
+            let speed_str = format!("⚡{}%", (speed_scale * 100f32).round());
+            speed.style().remove_property("display").to_anyhow()?;
+            speed.set_inner_html(&speed_str[..]);
         },
         _ => {
             score.style().set_property("display", "none").to_anyhow()?;
             lives.style().set_property("display", "none").to_anyhow()?;
+            speed.style().set_property("display", "none").to_anyhow()?;
         }
     };
 
+    let mute_button : HtmlElement = document.get_element_by_id("mute-button").unwrap().unchecked_into();
+    let mute_str = if game_state.borrow().audio.borrow().muted { "🔇" } else { "🔊" };
+
+    if mute_button.inner_html() != mute_str {
+        mute_button.set_inner_html(mute_str);
+    }
+
+    update_touch_buttons(&game_state.borrow(), &overlay)?;
+    update_pause_menu(&mut game_state.borrow_mut(), &overlay, time)?;
     update_game_over(&mut game_state.borrow_mut(), &overlay)?;
     update_score_board(game_state, &overlay)?;
 
     return Ok(());
 }
 
+/// Reads the gamepads and triggers the actions of the buttons pressed since the last frame.
+pub fn poll_gamepads(game_state : &Rc<RefCell<GameState>>) {
+    if let Err(error) = game_state.borrow_mut().gamepad.poll(GAMEPAD_DEAD_ZONE) {
+        log!("Failed to read the gamepads: {:?}", error);
+    }
+
+    let pressed = game_state.borrow().gamepad.pressed_buttons();
+
+    for button in pressed {
+        if let Some(action) = gamepad_action(button) {
+            GameState::trigger(game_state, action);
+        }
+    }
+}
+
 pub fn update(
     game_state_rc : &mut Rc<RefCell<GameState>>,
     time : f64) -> anyhow::Result<()> {
 
     if game_state_rc.borrow().reset_requested {
-        *game_state_rc = GameState::init(time);
+        let audio = game_state_rc.borrow().audio.clone();
+        *game_state_rc = GameState::init(time, audio);
+        bind_overlay(&game_state_rc.borrow())?;
     }
 
     {
         let mut borrow_mut = game_state_rc.borrow_mut();
-        let mut game_state : &mut GameState = borrow_mut.deref_mut();
+        let game_state : &mut GameState = borrow_mut.deref_mut();
+
+        let mut input = Input::default();
+        let mut pointer_target = None;
+
+        if game_state.is_action_held(Action::MoveLeft) {
+            input.direction = -1f32;
+        }
+        else if game_state.is_action_held(Action::MoveRight) {
+            input.direction = 1f32;
+        }
+        else {
+            // The stick moves the bat proportionally to how far it's pushed.
+            input.direction = game_state.gamepad.axis;
+        }
 
-        let left_arrow = game_state.keyboard_state.borrow().is_down("ArrowLeft");
-        let right_arrow = game_state.keyboard_state.borrow().is_down("ArrowRight");
+        let touch = game_state.touch_positions().first().map(|(identifier, position)| (*identifier, position.x));
+        let touch_target = game_state.touch_drag.target(game_state.touch_scheme, touch, game_state.simulation.bat.position.x);
 
-        game_state.bat.input = vec2(0f32, 0f32);
+        // The keys and the touches take over from the mouse until it moves again.
+        if input.direction != 0f32 || touch_target.is_some() {
+            game_state.pointer_tracker.borrow_mut().position = None;
+            pointer_target = touch_target.filter(|_| input.direction == 0f32);
+        }
+        else {
+            let pointer_tracker = game_state.pointer_tracker.borrow();
 
-        if left_arrow || right_arrow {
-            if left_arrow {
-                game_state.bat.input = vec2(-1f32, 0f32);
+            if let Some(position) = pointer_tracker.position {
+                let game_area = vec2(game_state.simulation.config.arena_width, game_state.simulation.config.arena_height);
+                pointer_target = Some(canvas_to_game(position, game_area, pointer_tracker.canvas_size).x);
             }
-            else {
-                game_state.bat.input = vec2(1f32, 0f32);
+        }
+
+        let document = web_sys::window().unwrap().document().unwrap();
+
+        if game_state.simulation.stage != GameStage::Gameplay && document.pointer_lock_element().is_some() {
+            document.exit_pointer_lock();
+        }
+
+        if game_state.pause_requested {
+            game_state.pause_requested = false;
+            game_state.resume_requested = false;
+            game_state.resume_time = None;
+            game_state.simulation.pause();
+        }
+
+        if game_state.resume_requested {
+            game_state.resume_requested = false;
+
+            if game_state.simulation.stage == GameStage::Paused && game_state.resume_time.is_none() {
+                game_state.resume_time = Some(time + RESUME_COUNTDOWN);
             }
         }
-        else {
-            for touch in &game_state.touch_tracker.borrow().touches {
-                if touch.client_x < 500i32 {
-                    game_state.bat.input = vec2(-1f32, 0f32);
-                }
-                else {
-                    game_state.bat.input = vec2(1f32, 0f32);
-                }
+
+        if let Some(resume_time) = game_state.resume_time {
+            if resume_time <= time {
+                game_state.resume_time = None;
+                game_state.simulation.resume();
             }
         }
 
-        let epsilon = 0.01f64;
+        // A paused game doesn't accumulate the time, so it doesn't have to catch up on resume.
+        if game_state.simulation.stage == GameStage::Paused {
+            game_state.last_time = time;
+            return Ok(());
+        }
+
         let mut current = game_state.last_time;
-        game_state.time.real_time = time;
+        let mut sounds = Vec::new();
+
+        while config::TIME_STEP < time - current {
+            let input = match &mut game_state.playback {
+                Some(playback) => playback.next().unwrap_or_default(),
+                None => match pointer_target {
+                    // Steered on every step, so the bat slows down smoothly near the target.
+                    Some(target) => Input { direction: steer_bat(&game_state.simulation.bat, target) },
+                    None => input
+                }
+            };
 
-        while epsilon < time - current {
-            game_state.time.sim_time += epsilon as f64;
-            game_state.time.elapsed = epsilon as f32;
+            let stage = game_state.simulation.stage;
 
-            match game_state.stage {
-                GameStage::Gameplay => {
-                    let ball_status = update_ball(&game_state.bat, &mut game_state.ball, &mut game_state.bricks, game_state.time.elapsed)?;
+            if game_state.playback.is_none() {
+                game_state.replay.record(&input);
+            }
 
-                    game_state.score += ball_status.brick_hit_count as i64;
+            gamelib::step(&mut game_state.simulation, &input)?;
 
-                    if ball_status.out_of_arena {
-                        decrease_lives(game_state, game_state.time);
-                    }
+            game_state.particles.spawn_events(&game_state.simulation);
+            game_state.particles.update(config::TIME_STEP as f32);
 
-                    update_bat(&mut game_state.bat, game_state.time.elapsed)?;
-                },
-                GameStage::GameOver => {
-                    if game_state.time.real_time - game_state.game_over_time > config::GAME_OVER_PAUSE_TIME {
-                        game_state.stage = GameStage::ScoreBoard
-                    }
+            for sound in &game_state.simulation.sounds {
+                if !sounds.contains(sound) {
+                    sounds.push(*sound);
                 }
-                _ => {}
-            };
+            }
+
+            if stage == GameStage::Gameplay && game_state.simulation.stage == GameStage::GameOver && game_state.playback.is_none() {
+                if let Err(error) = save_replay(&game_state.replay) {
+                    log!("Failed to save replay: {:?}", error);
+                }
+            }
 
-            game_state.bricks.update(game_state.time.elapsed)?;
-            current += epsilon as f64;
+            current += config::TIME_STEP;
         }
 
         game_state.last_time = current;
+
+        // Every sound is played once per frame, the bricks destroyed together make a single hit.
+        for sound in sounds {
+            if let Err(error) = game_state.audio.borrow().play(sound) {
+                log!("Failed to play sound: {:?}", error);
+            }
+        }
     }
 
     return Ok(());
 }
 
+/// Draws the game into the canvas of the `canvas_size` pixels.
 pub fn render(
     game_state : &GameState,
-    rendering_context : &CanvasRenderingContext2d,
+    renderer : &mut dyn Renderer,
     canvas_size : Vec2,
     _time : f64) -> anyhow::Result<()> {
-    let width = canvas_size.x as f64;
-    let height = canvas_size.y as f64;
+    return render_simulation(
+        &game_state.simulation,
+        &game_state.particles,
+        game_state.ball_trail,
+        renderer,
+        canvas_size);
+}
 
-    rendering_context.set_fill_style(&JsValue::from_str("lightgray"));
-    rendering_context.fill_rect(0.0, 0.0, width, height);
-    rendering_context.set_transform(
-        width / config::GAME_AREA_WIDTH,
-        0.0,
-        0.0,
-        height / config::GAME_AREA_HEIGHT,
-        0.0,
-        0.0).unwrap();
+/// The scale and the translation that map the game coordinates to the canvas pixels.
+pub fn game_transform(game_area : Vec2, canvas_size : Vec2) -> (Vec2, Vec2) {
+    return (canvas_size.component_div(&game_area), vec2(0f32, 0f32));
+}
 
+/// Maps a point in the canvas pixels back to the game coordinates.
+pub fn canvas_to_game(point : Vec2, game_area : Vec2, canvas_size : Vec2) -> Vec2 {
+    let (scale, translation) = game_transform(game_area, canvas_size);
+    return (point - translation).component_div(&scale);
+}
 
+/// Draws the simulation and the decorations that go with it, it doesn't need the browser.
+pub fn render_simulation(
+    simulation : &Simulation,
+    particles : &Particles,
+    ball_trail : bool,
+    renderer : &mut dyn Renderer,
+    canvas_size : Vec2) -> anyhow::Result<()> {
+    let game_area = vec2(simulation.config.arena_width, simulation.config.arena_height);
+
+    renderer.fill_rect(vec2(0f32, 0f32), canvas_size, "lightgray")?;
+    let (scale, translation) = game_transform(game_area, canvas_size);
+    renderer.set_transform(scale, translation)?;
+
+    match simulation.stage {
+        GameStage::Gameplay | GameStage::Paused | GameStage::GameOver => {
+            for entity in &simulation.bricks.bricks {
+                render_brick(entity, renderer)?;
+            }
+
+            render_particles(particles, renderer)?;
 
-    match game_state.stage {
-        GameStage::Gameplay | GameStage::GameOver => {
-            for entity in &game_state.bricks.bricks {
-                render_brick(entity, rendering_context)?;
+            for bonus in &simulation.bonuses {
+                render_bonus(bonus, renderer)?;
             }
 
-            render_ball(&game_state.ball, rendering_context)?;
+            for ball in &simulation.balls {
+                render_ball(ball, simulation.config.ball_velocity, renderer, ball_trail)?;
+            }
         },
         _ => ()
     };
 
-    match game_state.stage {
-        GameStage::Gameplay => render_bat(&game_state.bat, rendering_context)?,
+    match simulation.stage {
+        GameStage::Gameplay | GameStage::Paused => render_bat(&simulation.bat, renderer)?,
         _ => ()
     };
 
-    rendering_context.reset_transform().unwrap();
+    renderer.reset_transform()?;
 
     return Ok(());
 }
@@ -0,0 +1,154 @@
+use glm::*;
+use gamelib::Simulation;
+use gamelib::ball::*;
+use gamelib::bricks::*;
+use gamelib::random::*;
+use crate::utils::*;
+use crate::renderer::*;
+use crate::game::bricks::*;
+
+/// The number of particles alive at once, unless the `particles` query parameter says otherwise.
+pub const DEFAULT_PARTICLE_BUDGET : usize = 500;
+
+const PARTICLE_GRAVITY : f32 = 1500f32;
+const PARTICLE_DRAG : f32 = 2f32;
+
+const DEBRIS_PER_BRICK : u32 = 8;
+const DEBRIS_SPEED : f32 = 250f32;
+const DEBRIS_LIFETIME : f32 = 0.8f32;
+
+const SPARKS_PER_IMPACT : u32 = 6;
+const SPARK_SPEED : f32 = 400f32;
+const SPARK_LIFETIME : f32 = 0.25f32;
+
+pub struct Particle {
+    pub position : Vec2,
+    pub velocity : Vec2,
+    pub size : f32,
+    pub color : &'static str,
+    /// The seconds the particle has been alive for.
+    pub age : f32,
+    pub lifetime : f32
+}
+
+/// The debris and sparks of the game. They are only decorations, the simulation doesn't know
+/// about them.
+pub struct Particles {
+    pub particles : Vec<Particle>,
+    pub budget : usize,
+    random : Random
+}
+
+pub fn particle_budget() -> usize {
+    match query_parameter("particles") {
+        Ok(Some(value)) => value.parse().unwrap_or(DEFAULT_PARTICLE_BUDGET),
+        _ => DEFAULT_PARTICLE_BUDGET
+    }
+}
+
+impl Particles {
+    pub fn new(seed : u64, budget : usize) -> Particles {
+        Particles {
+            particles: Vec::new(),
+            budget: budget,
+            random: Random::new(seed)
+        }
+    }
+
+    fn random_range(&mut self, min : f32, max : f32) -> f32 {
+        min + (max - min) * self.random.next_f32()
+    }
+
+    /// Adds the particle, the oldest ones make room for it when the budget is exhausted.
+    fn spawn(&mut self, particle : Particle) {
+        if self.budget == 0 {
+            return;
+        }
+
+        if self.particles.len() >= self.budget {
+            self.particles.remove(0);
+        }
+
+        self.particles.push(particle);
+    }
+
+    fn spawn_debris(&mut self, brick : &Brick) {
+        for _ in 0..DEBRIS_PER_BRICK {
+            let offset = vec2(self.random_range(-0.5f32, 0.5f32), self.random_range(-0.5f32, 0.5f32));
+            let position = brick.position + brick.size.component_mul(&offset);
+            let velocity = vec2(offset.x * 2f32, self.random_range(-1f32, 0f32)) * DEBRIS_SPEED;
+            let size = self.random_range(3f32, 8f32);
+            let lifetime = self.random_range(0.5f32, 1f32) * DEBRIS_LIFETIME;
+
+            self.spawn(Particle {
+                position: position,
+                velocity: velocity,
+                size: size,
+                color: brick_color(brick.kind),
+                age: 0f32,
+                lifetime: lifetime
+            });
+        }
+    }
+
+    fn spawn_sparks(&mut self, impact : &Impact) {
+        let color = match impact.kind {
+            ImpactKind::Bat => "gold",
+            _ => "orange"
+        };
+
+        for _ in 0..SPARKS_PER_IMPACT {
+            let tangent = vec2(-impact.normal.y, impact.normal.x);
+            let spread = self.random_range(-1f32, 1f32);
+            let speed = self.random_range(0.5f32, 1f32) * SPARK_SPEED;
+            let velocity = normalize(&(impact.normal + tangent * spread)) * speed;
+            let lifetime = self.random_range(0.5f32, 1f32) * SPARK_LIFETIME;
+
+            self.spawn(Particle {
+                position: impact.position,
+                velocity: velocity,
+                size: 3f32,
+                color: color,
+                age: 0f32,
+                lifetime: lifetime
+            });
+        }
+    }
+
+    /// Spawns the particles of the events of the last simulation step.
+    pub fn spawn_events(&mut self, simulation : &Simulation) {
+        for brick in &simulation.bricks.destroyed {
+            self.spawn_debris(brick);
+        }
+
+        for impact in &simulation.impacts {
+            if impact.kind != ImpactKind::Brick {
+                self.spawn_sparks(impact);
+            }
+        }
+    }
+
+    pub fn update(&mut self, elapsed : f32) {
+        for particle in &mut self.particles {
+            particle.velocity += vec2(0f32, PARTICLE_GRAVITY * elapsed);
+            particle.velocity *= 1f32 - PARTICLE_DRAG * elapsed;
+            particle.position += particle.velocity * elapsed;
+            particle.age += elapsed;
+        }
+
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+    }
+}
+
+pub fn render_particles(particles : &Particles, renderer : &mut dyn Renderer) -> anyhow::Result<()> {
+    for particle in &particles.particles {
+        let size = vec2(particle.size, particle.size);
+
+        renderer.set_alpha(1f32 - particle.age / particle.lifetime)?;
+        renderer.fill_rect(particle.position - size * 0.5f32, size, particle.color)?;
+    }
+
+    renderer.set_alpha(1f32)?;
+
+    return Ok(());
+}
@@ -0,0 +1,55 @@
+use crate::utils::*;
+use crate::executor::*;
+use crate::game::GameState;
+use gamelib::Simulation;
+use gamelib::replay::*;
+use std::{rc::Rc, rc::Weak, cell::RefCell};
+
+const LAST_REPLAY_KEY : &'static str = "last-replay";
+
+/// Stores the replay in the local storage and prints it to the console, so it can be attached
+/// to a bug report.
+pub fn save_replay(replay : &Replay) -> anyhow::Result<()> {
+    let json = replay.to_json()?;
+
+    log!("replay: {}", json);
+
+    local_storage()?.set_item(LAST_REPLAY_KEY, json.as_str()).to_anyhow()?;
+
+    return Ok(());
+}
+
+pub fn start_playback(game_state : &mut GameState, replay : Replay) -> anyhow::Result<()> {
+    game_state.simulation = Simulation::with_config(replay.seed, replay.mode, replay.level.clone(), replay.config.clone())?;
+    game_state.playback = Some(replay.player());
+    game_state.session_id = None;
+    game_state.replay = Replay::with_config(replay.seed, replay.mode, replay.level.clone(), replay.config.clone());
+
+    return Ok(());
+}
+
+pub async fn load_replay_inner(game_state : Weak<RefCell<GameState>>, uri : String) -> anyhow::Result<()> {
+    let replay = crate::webapi::fetch_replay(uri).await?;
+
+    if let Some(game_state) = game_state.upgrade() {
+        start_playback(&mut game_state.borrow_mut(), replay)?;
+    }
+
+    return Ok(());
+}
+
+pub async fn load_replay_async(game_state : Weak<RefCell<GameState>>, uri : String) {
+    match load_replay_inner(game_state, uri).await {
+        Err(error) => log!("Failed to load replay: {:?}", error),
+        Ok(_) => ()
+    }
+}
+
+/// Replaces the live game with the replay from the `replay` query parameter, if there is one.
+pub fn load_replay(game_state : &Rc<RefCell<GameState>>) -> anyhow::Result<()> {
+    if let Some(uri) = query_parameter("replay")? {
+        execute(load_replay_async(Rc::downgrade(game_state), uri));
+    }
+
+    return Ok(());
+}
@@ -4,9 +4,12 @@ use wasm_bindgen::JsCast;
 use crate::webapi::*;
 use crate::executor::*;
 use apilib::*;
+use crate::game::GameState;
+use gamelib::GameStage;
+use gamelib::replay::{Replay, simulate};
 use hex;
 use rand::prelude::*;
-use std::{rc::Rc, cell::RefCell};
+use std::{rc::Rc, rc::Weak, cell::RefCell};
 use uuid::Uuid;
 
 pub fn generate_seed() -> anyhow::Result<u64> {
@@ -47,18 +50,82 @@ pub async fn proof_of_work_async(session_id : [u8; 32], seed : u64, degree : usi
     }
 }
 
-pub async fn create_scoreboard_inner(
-    emitter : js_sys::Function,
-    new_score : i64,
-    score_id : Rc<RefCell<Uuid>>,
-    score_board_id : String) -> anyhow::Result<()> {
+/// Restarts the game with the seed of the session, if the ball is still waiting for the first
+/// launch. The inputs recorded so far are played again, so the bat stays where it was moved.
+/// A game that went further keeps its seed, but its score isn't submitted.
+pub fn start_session(game_state : &mut GameState, session_id : String) -> anyhow::Result<()> {
+    let simulation = &game_state.simulation;
+
+    let started = game_state.playback.is_some()
+        || simulation.score != 0
+        || simulation.lives != simulation.config.num_lives
+        || simulation.balls.iter().any(|ball| ball.freeze_time.is_none())
+        || (simulation.stage != GameStage::Gameplay && simulation.stage != GameStage::Paused);
+
+    if started {
+        log!("The session was issued after the game started, the score won't be submitted.");
+        return Ok(());
+    }
+
+    let mut decoded_session_id = [0u8; 32];
+    hex::decode_to_slice(session_id.as_str(), &mut decoded_session_id)?;
+
+    let mut replay = game_state.replay.clone();
+    replay.seed = session_seed(decoded_session_id);
+
+    let paused = simulation.stage == GameStage::Paused;
+    let mut simulation = simulate(&replay)?;
+
+    if paused {
+        simulation.pause();
+    }
+
+    game_state.simulation = simulation;
+    game_state.replay = replay;
+    game_state.session_id = Some(session_id);
+
+    return Ok(());
+}
 
+pub async fn request_session_inner(game_state : Weak<RefCell<GameState>>) -> anyhow::Result<()> {
     log!("Getting session id...");
 
     let session_id = new_session_id().await?;
 
     log!("session id: {:?}", session_id);
 
+    if let Some(game_state) = game_state.upgrade() {
+        start_session(&mut game_state.borrow_mut(), session_id)?;
+    }
+
+    return Ok(());
+}
+
+pub async fn request_session_async(game_state : Weak<RefCell<GameState>>) {
+    match request_session_inner(game_state).await {
+        Err(error) => log!("Failed to get a session: {:?}", error),
+        Ok(_) => ()
+    }
+}
+
+pub async fn create_scoreboard_inner(
+    emitter : js_sys::Function,
+    new_score : i64,
+    replay : Replay,
+    session_id : Option<String>,
+    score_id : Rc<RefCell<Uuid>>,
+    score_board_id : String) -> anyhow::Result<()> {
+
+    let session_id = match session_id {
+        Some(session_id) => session_id,
+        None => {
+            create_scoreboard_message_html(
+                "Score not submitted, the game started before the session was issued.",
+                score_board_id)?;
+            return Ok(());
+        }
+    };
+
     let mut decoded_session_id = [0u8; 32];
     hex::decode_to_slice(session_id.as_str(), &mut decoded_session_id)?;
 
@@ -68,6 +135,7 @@ pub async fn create_scoreboard_inner(
 
     let response = crate::webapi::new_score(&NewScoreRequest {
         score : new_score,
+        replay : replay,
         session_id : session_id,
         proof_of_work : hex::encode_upper(proof),
         limit : 10i64
@@ -80,8 +148,8 @@ pub async fn create_scoreboard_inner(
             create_scoreboard_html(emitter, index, scores, score_board_id).await?;
             *score_id.borrow_mut() = id;
         },
-        NewScoreResponse::Error(_) =>
-            ()
+        NewScoreResponse::Error(error) =>
+            create_scoreboard_message_html(format!("Score rejected: {}", error).as_str(), score_board_id)?
     };
 
     return Ok(());
@@ -135,6 +203,25 @@ pub async fn create_scoreboard_html(
     return Ok(());
 }
 
+/// Shows the message in place of the high scores, the text isn't parsed as HTML.
+pub fn create_scoreboard_message_html(message : &str, score_board_id : String) -> anyhow::Result<()> {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+    let overlay : HtmlElement = document.get_element_by_id("main-overlay-id")
+        .unwrap().unchecked_into();
+
+    let paragraph = document.create_element("p").to_anyhow()?;
+    paragraph.set_class_name("font-large");
+    paragraph.set_text_content(Some(message));
+
+    let score_board = document.get_element_by_id(score_board_id.as_ref()).unwrap();
+    score_board.set_inner_html("");
+    score_board.append_child(&paragraph).to_anyhow()?;
+    overlay.append_child(&score_board).to_anyhow()?;
+
+    return Ok(());
+}
+
 pub fn collapse_scoreboard_input_html(overlay : &HtmlElement) -> anyhow::Result<()> {
     let document = overlay.owner_document().unwrap();
 
@@ -161,12 +248,17 @@ pub fn collapse_scoreboard_input_html(overlay : &HtmlElement) -> anyhow::Result<
 pub async fn populate_scoreboard(
     emitter : js_sys::Function,
     new_score : i64,
+    replay : Replay,
+    session_id : Option<String>,
     score_id : Rc<RefCell<Uuid>>,
     score_board_id : String) {
-    let result1 = create_scoreboard_inner(emitter, new_score, score_id, score_board_id.clone()).await;
+    let result1 = create_scoreboard_inner(emitter, new_score, replay, session_id, score_id, score_board_id.clone()).await;
 
     match result1 {
-        Err(error) => log!("Failed to create scoreboard: {:?}", error),
+        Err(error) => {
+            log!("Failed to create scoreboard: {:?}", error);
+            create_scoreboard_message_html("Score not submitted, the server can't be reached.", score_board_id).ok();
+        },
         Ok(_) => ()
     }
 }
@@ -174,6 +266,8 @@ pub async fn populate_scoreboard(
 pub fn create_scoreboard(
     emitter : js_sys::Function,
     new_score : i64,
+    replay : Replay,
+    session_id : Option<String>,
     score_id : Rc<RefCell<Uuid>>,
     score_board_id : &str) -> anyhow::Result<()> {
 
@@ -187,7 +281,7 @@ pub fn create_scoreboard(
 
     overlay.append_child(&score_board).to_anyhow()?;
 
-    let future = populate_scoreboard(emitter, new_score, score_id, score_board_id.to_owned());
+    let future = populate_scoreboard(emitter, new_score, replay, session_id, score_id, score_board_id.to_owned());
 
     execute(future);
 
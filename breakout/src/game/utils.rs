@@ -2,13 +2,6 @@ use web_sys::*;
 use wasm_bindgen::{JsCast};
 use anyhow;
 
-#[derive(Copy, Clone)]
-pub struct GameTime {
-    pub sim_time : f64,
-    pub real_time : f64,
-    pub elapsed : f32
-}
-
 pub fn create_style_element(document : &Document, sheet : &str, id : &str) -> anyhow::Result<HtmlStyleElement> {
     let style : HtmlStyleElement = document.create_element("style").unwrap().unchecked_into();
 
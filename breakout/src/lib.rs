@@ -2,17 +2,22 @@ extern crate nalgebra_glm as glm;
 #[macro_use]
 pub mod utils;
 mod event;
-mod game;
-mod collision;
+pub mod game;
+pub mod renderer;
+pub mod rasterizer;
+pub mod png;
 mod webapi;
 mod executor;
 
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use std::include_str;
+use std::rc::Rc;
+use std::cell::RefCell;
 use web_sys::*;
 use utils::*;
 use game::*;
+use renderer::*;
 use glm::vec2;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
@@ -97,6 +102,14 @@ fn update_viewport_size() {
 
     let common_div : HtmlElement = document.get_element_by_id("common-div").unwrap().unchecked_into();
 
+    // The virtual keyboard of a phone shrinks the viewport, the game keeps its size while the
+    // player types the nickname.
+    if let Some(element) = document.active_element() {
+        if element.tag_name() == "INPUT" {
+            return;
+        }
+    }
+
     let client_width = root.client_width() as f32;
     let client_height = root.client_height() as f32;
 
@@ -203,13 +216,14 @@ impl Application {
         let canvas_size = vec2(width as f32, height as f32);
 
         if self.game_state.is_none() {
-            self.game_state = Some(GameState::init(time));
+            self.game_state = Some(GameState::init(time, Rc::new(RefCell::new(AudioPlayer::new()))));
             game::init_overlay(&mut self.game_state.as_mut().unwrap().borrow_mut(), time)?;
         }
 
+        game::poll_gamepads(self.game_state.as_ref().unwrap());
         game::update(&mut self.game_state.as_mut().unwrap(), time)?;
         game::update_overlay(self.game_state.as_mut().unwrap(), time)?;
-        game::render(&mut self.game_state.as_mut().unwrap().borrow_mut(), &rendering_context, canvas_size, time)?;
+        game::render(&self.game_state.as_ref().unwrap().borrow(), &mut CanvasRenderer::new(rendering_context), canvas_size, time)?;
 
         return Ok(());
     }
@@ -0,0 +1,232 @@
+//! A minimal PNG encoder. The image data is compressed with the fixed Huffman codes of deflate
+//! and the runs of repeated bytes only, which is enough for the flat colors of the game.
+
+const PNG_SIGNATURE : [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+const MAX_MATCH_LENGTH : usize = 258;
+const MIN_MATCH_LENGTH : usize = 3;
+
+/// The first length of every deflate length code from 257 to 285 and its number of extra bits.
+const LENGTH_CODES : [(usize, u32); 29] = [
+    (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0),
+    (11, 1), (13, 1), (15, 1), (17, 1),
+    (19, 2), (23, 2), (27, 2), (31, 2),
+    (35, 3), (43, 3), (51, 3), (59, 3),
+    (67, 4), (83, 4), (99, 4), (115, 4),
+    (131, 5), (163, 5), (195, 5), (227, 5),
+    (258, 0)
+];
+
+fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+
+    for n in 0..256 {
+        let mut c = n as u32;
+
+        for _ in 0..8 {
+            c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+        }
+
+        table[n] = c;
+    }
+
+    return table;
+}
+
+pub fn crc32(data : &[u8]) -> u32 {
+    let table = crc32_table();
+    let mut crc = 0xffffffffu32;
+
+    for byte in data {
+        crc = table[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+
+    return crc ^ 0xffffffff;
+}
+
+pub fn adler32(data : &[u8]) -> u32 {
+    let mut a = 1u32;
+    let mut b = 0u32;
+
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    return (b << 16) | a;
+}
+
+/// Writes the bits starting from the least significant one, as deflate expects.
+struct BitWriter {
+    bytes : Vec<u8>,
+    bit_buffer : u32,
+    num_bits : u32
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter { bytes: Vec::new(), bit_buffer: 0, num_bits: 0 }
+    }
+
+    fn write_bits(&mut self, value : u32, num_bits : u32) {
+        self.bit_buffer |= value << self.num_bits;
+        self.num_bits += num_bits;
+
+        while self.num_bits >= 8 {
+            self.bytes.push((self.bit_buffer & 0xff) as u8);
+            self.bit_buffer >>= 8;
+            self.num_bits -= 8;
+        }
+    }
+
+    /// The Huffman codes are stored starting from the most significant bit.
+    fn write_code(&mut self, code : u32, num_bits : u32) {
+        let mut reversed = 0u32;
+
+        for i in 0..num_bits {
+            reversed |= ((code >> i) & 1) << (num_bits - 1 - i);
+        }
+
+        self.write_bits(reversed, num_bits);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.num_bits > 0 {
+            self.bytes.push((self.bit_buffer & 0xff) as u8);
+        }
+
+        return self.bytes;
+    }
+}
+
+fn write_symbol(writer : &mut BitWriter, symbol : u32) {
+    match symbol {
+        0..=143 => writer.write_code(0x30 + symbol, 8),
+        144..=255 => writer.write_code(0x190 + symbol - 144, 9),
+        256..=279 => writer.write_code(symbol - 256, 7),
+        _ => writer.write_code(0xc0 + symbol - 280, 8)
+    }
+}
+
+/// Repeats the previous byte `length` times.
+fn write_run(writer : &mut BitWriter, length : usize) {
+    let index = LENGTH_CODES.iter().rposition(|(base, _)| *base <= length).unwrap();
+    let (base, extra_bits) = LENGTH_CODES[index];
+
+    write_symbol(writer, 257 + index as u32);
+    writer.write_bits((length - base) as u32, extra_bits);
+
+    // The distance of one byte has the distance code zero.
+    writer.write_code(0, 5);
+}
+
+pub fn deflate(data : &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+
+    // The last block, compressed with the fixed codes.
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let mut i = 0;
+
+    while i < data.len() {
+        write_symbol(&mut writer, data[i] as u32);
+
+        let mut run = 0;
+
+        while i + 1 + run < data.len() && data[i + 1 + run] == data[i] && run < MAX_MATCH_LENGTH {
+            run += 1;
+        }
+
+        if run >= MIN_MATCH_LENGTH {
+            write_run(&mut writer, run);
+            i += 1 + run;
+        }
+        else {
+            i += 1;
+        }
+    }
+
+    write_symbol(&mut writer, 256);
+
+    return writer.finish();
+}
+
+fn zlib_compress(data : &[u8]) -> Vec<u8> {
+    let mut result = vec![0x78, 0x01];
+    result.extend(deflate(data));
+    result.extend(&adler32(data).to_be_bytes());
+    return result;
+}
+
+fn write_chunk(png : &mut Vec<u8>, kind : &[u8; 4], data : &[u8]) {
+    let mut chunk = kind.to_vec();
+    chunk.extend(data);
+
+    png.extend(&(data.len() as u32).to_be_bytes());
+    png.extend(&chunk);
+    png.extend(&crc32(&chunk).to_be_bytes());
+}
+
+fn paeth(a : u8, b : u8, c : u8) -> u8 {
+    let p = a as i32 + b as i32 - c as i32;
+    let pa = (p - a as i32).abs();
+    let pb = (p - b as i32).abs();
+    let pc = (p - c as i32).abs();
+
+    if pa <= pb && pa <= pc { a } else if pb <= pc { b } else { c }
+}
+
+/// Applies the PNG filter that leaves the smallest differences to every row.
+fn filter_rows(width : u32, height : u32, rgba : &[u8]) -> Vec<u8> {
+    let stride = width as usize * 4;
+    let mut result = Vec::with_capacity((stride + 1) * height as usize);
+    let zeros = vec![0u8; stride];
+
+    for y in 0..height as usize {
+        let row = &rgba[y * stride..(y + 1) * stride];
+        let above = if y == 0 { &zeros[..] } else { &rgba[(y - 1) * stride..y * stride] };
+
+        let filtered : Vec<Vec<u8>> = (0..5).map(|filter| {
+            (0..stride).map(|x| {
+                let left = if x < 4 { 0 } else { row[x - 4] };
+                let upper_left = if x < 4 { 0 } else { above[x - 4] };
+
+                let prediction = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => above[x],
+                    3 => ((left as u32 + above[x] as u32) / 2) as u8,
+                    _ => paeth(left, above[x], upper_left)
+                };
+
+                row[x].wrapping_sub(prediction)
+            }).collect()
+        }).collect();
+
+        let cost = |row : &Vec<u8>| row.iter().map(|byte| (*byte as i8).unsigned_abs() as u32).sum::<u32>();
+        let best = (0..5).min_by_key(|filter| cost(&filtered[*filter])).unwrap();
+
+        result.push(best as u8);
+        result.extend(&filtered[best]);
+    }
+
+    return result;
+}
+
+/// Encodes the 8-bit RGBA pixels, row by row from the top, as a PNG file.
+pub fn encode_png(width : u32, height : u32, rgba : &[u8]) -> Vec<u8> {
+    let mut png = PNG_SIGNATURE.to_vec();
+
+    let mut header = Vec::new();
+    header.extend(&width.to_be_bytes());
+    header.extend(&height.to_be_bytes());
+    // The bit depth, the RGBA color type and the default compression, filtering and interlacing.
+    header.extend(&[8, 6, 0, 0, 0]);
+
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_compress(&filter_rows(width, height, rgba)));
+    write_chunk(&mut png, b"IEND", &[]);
+
+    return png;
+}
@@ -0,0 +1,237 @@
+use glm::*;
+use crate::renderer::*;
+use crate::png::*;
+use crate::game::render_simulation;
+use crate::game::particles::Particles;
+use gamelib::Simulation;
+
+/// 8-bit RGBA pixels, row by row from the top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width : u32,
+    pub height : u32,
+    pub pixels : Vec<u8>
+}
+
+impl Image {
+    pub fn new(width : u32, height : u32) -> Image {
+        Image { width: width, height: height, pixels: vec![0u8; (width * height * 4) as usize] }
+    }
+
+    pub fn pixel(&self, x : u32, y : u32) -> [u8; 4] {
+        let index = ((y * self.width + x) * 4) as usize;
+        return [self.pixels[index], self.pixels[index + 1], self.pixels[index + 2], self.pixels[index + 3]];
+    }
+
+    /// Draws the color over the pixel with the `alpha` opacity.
+    pub fn blend_pixel(&mut self, x : u32, y : u32, color : [u8; 3], alpha : f32) {
+        let index = ((y * self.width + x) * 4) as usize;
+
+        for channel in 0..3 {
+            let old = self.pixels[index + channel] as f32;
+            self.pixels[index + channel] = (old + (color[channel] as f32 - old) * alpha).round() as u8;
+        }
+
+        let old = self.pixels[index + 3] as f32;
+        self.pixels[index + 3] = (old + (255f32 - old) * alpha).round() as u8;
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        encode_png(self.width, self.height, &self.pixels)
+    }
+
+    pub fn save_png(&self, path : &str) -> anyhow::Result<()> {
+        std::fs::write(path, self.to_png())?;
+        return Ok(());
+    }
+}
+
+/// The colors used by the game.
+const COLORS : [(&'static str, [u8; 3]); 16] = [
+    ("black", [0, 0, 0]),
+    ("white", [255, 255, 255]),
+    ("red", [255, 0, 0]),
+    ("green", [0, 128, 0]),
+    ("blue", [0, 0, 255]),
+    ("gold", [255, 215, 0]),
+    ("orange", [255, 165, 0]),
+    ("darkorange", [255, 140, 0]),
+    ("purple", [128, 0, 128]),
+    ("deeppink", [255, 20, 147]),
+    ("gray", [128, 128, 128]),
+    ("grey", [128, 128, 128]),
+    ("dimgray", [105, 105, 105]),
+    ("slategray", [112, 128, 144]),
+    ("lightgray", [211, 211, 211]),
+    ("darkred", [139, 0, 0])
+];
+
+pub fn parse_color(color : &str) -> anyhow::Result<[u8; 3]> {
+    match COLORS.iter().find(|(name, _)| *name == color) {
+        Some((_, rgb)) => Ok(*rgb),
+        None => Err(anyhow::anyhow!("Unknown color '{}'!", color))
+    }
+}
+
+/// The digits of a 3x5 pixel font, a row per byte with the leftmost pixel in the third bit.
+const DIGITS : [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111]
+];
+
+/// Draws into an `Image` on the CPU, so the frames can be rendered without a browser. The
+/// shapes are not anti-aliased, a pixel is covered if its center is inside the shape.
+pub struct SoftwareRenderer {
+    pub image : Image,
+    scale : Vec2,
+    translation : Vec2,
+    alpha : f32
+}
+
+impl SoftwareRenderer {
+    pub fn new(width : u32, height : u32) -> SoftwareRenderer {
+        SoftwareRenderer {
+            image: Image::new(width, height),
+            scale: vec2(1f32, 1f32),
+            translation: vec2(0f32, 0f32),
+            alpha: 1f32
+        }
+    }
+
+    fn to_canvas(&self, point : Vec2) -> Vec2 {
+        point.component_mul(&self.scale) + self.translation
+    }
+
+    fn to_user(&self, point : Vec2) -> Vec2 {
+        (point - self.translation).component_div(&self.scale)
+    }
+
+    /// Blends the color into the pixels of the user space rectangle from `min` to `max` whose
+    /// centers pass the `inside` test, the test gets the center in the user space.
+    fn fill<F : Fn(Vec2) -> bool>(&mut self, min : Vec2, max : Vec2, color : &str, inside : F) -> anyhow::Result<()> {
+        let color = parse_color(color)?;
+        let a = self.to_canvas(min);
+        let b = self.to_canvas(max);
+        let clamp = |value : f32, limit : u32| value.max(0f32).min(limit as f32) as u32;
+
+        let x_begin = clamp(a.x.min(b.x) - 0.5f32, self.image.width);
+        let x_end = clamp((a.x.max(b.x) + 0.5f32).ceil(), self.image.width);
+        let y_begin = clamp(a.y.min(b.y) - 0.5f32, self.image.height);
+        let y_end = clamp((a.y.max(b.y) + 0.5f32).ceil(), self.image.height);
+
+        for y in y_begin..y_end {
+            for x in x_begin..x_end {
+                if inside(self.to_user(vec2(x as f32 + 0.5f32, y as f32 + 0.5f32))) {
+                    self.image.blend_pixel(x, y, color, self.alpha);
+                }
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+fn segment_distance(point : Vec2, from : Vec2, to : Vec2) -> f32 {
+    let direction = to - from;
+    let length2 = dot(&direction, &direction);
+
+    let t = if length2 == 0f32 { 0f32 } else { dot(&(point - from), &direction) / length2 };
+    let t = t.max(0f32).min(1f32);
+
+    return distance(&point, &(from + direction * t));
+}
+
+impl Renderer for SoftwareRenderer {
+    fn set_transform(&mut self, scale : Vec2, translation : Vec2) -> anyhow::Result<()> {
+        self.scale = scale;
+        self.translation = translation;
+        return Ok(());
+    }
+
+    fn reset_transform(&mut self) -> anyhow::Result<()> {
+        return self.set_transform(vec2(1f32, 1f32), vec2(0f32, 0f32));
+    }
+
+    fn set_alpha(&mut self, alpha : f32) -> anyhow::Result<()> {
+        self.alpha = alpha.max(0f32).min(1f32);
+        return Ok(());
+    }
+
+    fn fill_rect(&mut self, origin : Vec2, size : Vec2, color : &str) -> anyhow::Result<()> {
+        let end = origin + size;
+        return self.fill(origin, end, color, |p| origin.x <= p.x && p.x < end.x && origin.y <= p.y && p.y < end.y);
+    }
+
+    fn stroke_rect(&mut self, origin : Vec2, size : Vec2, line_width : f32, color : &str) -> anyhow::Result<()> {
+        let half = line_width * 0.5f32;
+        let outer_min = origin - vec2(half, half);
+        let outer_max = origin + size + vec2(half, half);
+        let inner_min = origin + vec2(half, half);
+        let inner_max = origin + size - vec2(half, half);
+
+        return self.fill(outer_min, outer_max, color, |p| {
+            let outer = outer_min.x <= p.x && p.x < outer_max.x && outer_min.y <= p.y && p.y < outer_max.y;
+            let inner = inner_min.x <= p.x && p.x < inner_max.x && inner_min.y <= p.y && p.y < inner_max.y;
+            outer && !inner
+        });
+    }
+
+    fn fill_circle(&mut self, center : Vec2, radius : f32, color : &str) -> anyhow::Result<()> {
+        let extent = vec2(radius, radius);
+        return self.fill(center - extent, center + extent, color, |p| distance(&p, &center) <= radius);
+    }
+
+    fn line(&mut self, from : Vec2, to : Vec2, width : f32, color : &str) -> anyhow::Result<()> {
+        let half = width * 0.5f32;
+        let extent = vec2(half, half);
+        let min = vec2(from.x.min(to.x), from.y.min(to.y)) - extent;
+        let max = vec2(from.x.max(to.x), from.y.max(to.y)) + extent;
+
+        return self.fill(min, max, color, |p| segment_distance(p, from, to) <= half);
+    }
+
+    /// Only the digits are drawn, the other characters are left as gaps.
+    fn text(&mut self, text : &str, center : Vec2, size : f32, color : &str) -> anyhow::Result<()> {
+        let pixel = size * 0.7f32 / 5f32;
+        let advance = pixel * 4f32;
+        let width = advance * text.chars().count() as f32 - pixel;
+        let origin = center - vec2(width, pixel * 5f32) * 0.5f32;
+
+        for (index, character) in text.chars().enumerate() {
+            let glyph = match character.to_digit(10) {
+                Some(digit) => DIGITS[digit as usize],
+                None => continue
+            };
+
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        let position = origin + vec2(index as f32 * advance + col as f32 * pixel, row as f32 * pixel);
+                        self.fill_rect(position, vec2(pixel, pixel), color)?;
+                    }
+                }
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+/// Renders the simulation into an image of the `width` x `height` pixels, for example a share
+/// image or a thumbnail of the final board. The particles and the trails are left out.
+pub fn screenshot(simulation : &Simulation, width : u32, height : u32) -> anyhow::Result<Image> {
+    let mut renderer = SoftwareRenderer::new(width, height);
+    let particles = Particles::new(simulation.seed, 0);
+
+    render_simulation(simulation, &particles, false, &mut renderer, vec2(width as f32, height as f32))?;
+
+    return Ok(renderer.image);
+}
@@ -0,0 +1,208 @@
+use glm::*;
+use crate::utils::*;
+use wasm_bindgen::JsValue;
+
+/// The drawing operations used by the game. The coordinates are mapped through the current
+/// transform, the colors are CSS color names.
+pub trait Renderer {
+    /// Maps the point `p` to `p * scale + translation`.
+    fn set_transform(&mut self, scale : Vec2, translation : Vec2) -> anyhow::Result<()>;
+    fn reset_transform(&mut self) -> anyhow::Result<()>;
+    /// The opacity of the following draw calls in the `[0, 1]` range.
+    fn set_alpha(&mut self, alpha : f32) -> anyhow::Result<()>;
+    fn fill_rect(&mut self, origin : Vec2, size : Vec2, color : &str) -> anyhow::Result<()>;
+    fn stroke_rect(&mut self, origin : Vec2, size : Vec2, line_width : f32, color : &str) -> anyhow::Result<()>;
+    fn fill_circle(&mut self, center : Vec2, radius : f32, color : &str) -> anyhow::Result<()>;
+    /// A line with round ends.
+    fn line(&mut self, from : Vec2, to : Vec2, width : f32, color : &str) -> anyhow::Result<()>;
+    /// A bold text centered at the `center`, `size` is the height of the font.
+    fn text(&mut self, text : &str, center : Vec2, size : f32, color : &str) -> anyhow::Result<()>;
+}
+
+pub struct CanvasRenderer {
+    context : web_sys::CanvasRenderingContext2d
+}
+
+impl CanvasRenderer {
+    pub fn new(context : web_sys::CanvasRenderingContext2d) -> CanvasRenderer {
+        CanvasRenderer { context: context }
+    }
+}
+
+impl Renderer for CanvasRenderer {
+    fn set_transform(&mut self, scale : Vec2, translation : Vec2) -> anyhow::Result<()> {
+        return self.context.set_transform(
+            scale.x as f64,
+            0.0,
+            0.0,
+            scale.y as f64,
+            translation.x as f64,
+            translation.y as f64).to_anyhow();
+    }
+
+    fn reset_transform(&mut self) -> anyhow::Result<()> {
+        return self.context.reset_transform().to_anyhow();
+    }
+
+    fn set_alpha(&mut self, alpha : f32) -> anyhow::Result<()> {
+        self.context.set_global_alpha(alpha as f64);
+        return Ok(());
+    }
+
+    fn fill_rect(&mut self, origin : Vec2, size : Vec2, color : &str) -> anyhow::Result<()> {
+        self.context.set_fill_style(&JsValue::from_str(color));
+        self.context.fill_rect(origin.x as f64, origin.y as f64, size.x as f64, size.y as f64);
+        return Ok(());
+    }
+
+    fn stroke_rect(&mut self, origin : Vec2, size : Vec2, line_width : f32, color : &str) -> anyhow::Result<()> {
+        self.context.set_stroke_style(&JsValue::from_str(color));
+        self.context.set_line_width(line_width as f64);
+        self.context.stroke_rect(origin.x as f64, origin.y as f64, size.x as f64, size.y as f64);
+        return Ok(());
+    }
+
+    fn fill_circle(&mut self, center : Vec2, radius : f32, color : &str) -> anyhow::Result<()> {
+        self.context.begin_path();
+        self.context.arc(center.x as f64, center.y as f64, radius as f64, 0.0, two_pi()).to_anyhow()?;
+        self.context.set_fill_style(&JsValue::from_str(color));
+        self.context.fill();
+        return Ok(());
+    }
+
+    fn line(&mut self, from : Vec2, to : Vec2, width : f32, color : &str) -> anyhow::Result<()> {
+        self.context.begin_path();
+        self.context.set_line_width(width as f64);
+        self.context.set_line_cap("round");
+        self.context.move_to(from.x as f64, from.y as f64);
+        self.context.line_to(to.x as f64, to.y as f64);
+        self.context.set_stroke_style(&JsValue::from_str(color));
+        self.context.stroke();
+        self.context.set_line_cap("butt");
+        return Ok(());
+    }
+
+    fn text(&mut self, text : &str, center : Vec2, size : f32, color : &str) -> anyhow::Result<()> {
+        self.context.set_fill_style(&JsValue::from_str(color));
+        self.context.set_font(format!("bold {}px sans-serif", size as i32).as_str());
+        self.context.set_text_align("center");
+        self.context.set_text_baseline("middle");
+        return self.context.fill_text(text, center.x as f64, center.y as f64).to_anyhow();
+    }
+}
+
+/// A single call of the `Renderer` methods.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    SetTransform { scale : Vec2, translation : Vec2 },
+    ResetTransform,
+    SetAlpha { alpha : f32 },
+    FillRect { origin : Vec2, size : Vec2, color : String },
+    StrokeRect { origin : Vec2, size : Vec2, line_width : f32, color : String },
+    FillCircle { center : Vec2, radius : f32, color : String },
+    Line { from : Vec2, to : Vec2, width : f32, color : String },
+    Text { text : String, center : Vec2, size : f32, color : String }
+}
+
+/// Records the draw calls instead of drawing, so the rendering can be tested without a browser.
+#[derive(Default)]
+pub struct RecordingRenderer {
+    pub commands : Vec<DrawCommand>
+}
+
+impl RecordingRenderer {
+    pub fn new() -> RecordingRenderer {
+        RecordingRenderer { commands: Vec::new() }
+    }
+
+    /// The commands one per line, in a form that is easy to compare with a stored snapshot.
+    pub fn to_snapshot(&self) -> String {
+        let mut snapshot = String::new();
+
+        for command in &self.commands {
+            snapshot.push_str(format_command(command).as_str());
+            snapshot.push('\n');
+        }
+
+        return snapshot;
+    }
+}
+
+fn format_vec2(v : Vec2) -> String {
+    format!("({}, {})", v.x, v.y)
+}
+
+fn format_command(command : &DrawCommand) -> String {
+    match command {
+        DrawCommand::SetTransform { scale, translation } =>
+            format!("set_transform scale={} translation={}", format_vec2(*scale), format_vec2(*translation)),
+        DrawCommand::ResetTransform =>
+            "reset_transform".to_string(),
+        DrawCommand::SetAlpha { alpha } =>
+            format!("set_alpha {}", alpha),
+        DrawCommand::FillRect { origin, size, color } =>
+            format!("fill_rect origin={} size={} {}", format_vec2(*origin), format_vec2(*size), color),
+        DrawCommand::StrokeRect { origin, size, line_width, color } =>
+            format!("stroke_rect origin={} size={} width={} {}", format_vec2(*origin), format_vec2(*size), line_width, color),
+        DrawCommand::FillCircle { center, radius, color } =>
+            format!("fill_circle center={} radius={} {}", format_vec2(*center), radius, color),
+        DrawCommand::Line { from, to, width, color } =>
+            format!("line from={} to={} width={} {}", format_vec2(*from), format_vec2(*to), width, color),
+        DrawCommand::Text { text, center, size, color } =>
+            format!("text {:?} center={} size={} {}", text, format_vec2(*center), size, color)
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn set_transform(&mut self, scale : Vec2, translation : Vec2) -> anyhow::Result<()> {
+        self.commands.push(DrawCommand::SetTransform { scale: scale, translation: translation });
+        return Ok(());
+    }
+
+    fn reset_transform(&mut self) -> anyhow::Result<()> {
+        self.commands.push(DrawCommand::ResetTransform);
+        return Ok(());
+    }
+
+    fn set_alpha(&mut self, alpha : f32) -> anyhow::Result<()> {
+        self.commands.push(DrawCommand::SetAlpha { alpha: alpha });
+        return Ok(());
+    }
+
+    fn fill_rect(&mut self, origin : Vec2, size : Vec2, color : &str) -> anyhow::Result<()> {
+        self.commands.push(DrawCommand::FillRect { origin: origin, size: size, color: color.to_owned() });
+        return Ok(());
+    }
+
+    fn stroke_rect(&mut self, origin : Vec2, size : Vec2, line_width : f32, color : &str) -> anyhow::Result<()> {
+        self.commands.push(DrawCommand::StrokeRect {
+            origin: origin,
+            size: size,
+            line_width: line_width,
+            color: color.to_owned()
+        });
+
+        return Ok(());
+    }
+
+    fn fill_circle(&mut self, center : Vec2, radius : f32, color : &str) -> anyhow::Result<()> {
+        self.commands.push(DrawCommand::FillCircle { center: center, radius: radius, color: color.to_owned() });
+        return Ok(());
+    }
+
+    fn line(&mut self, from : Vec2, to : Vec2, width : f32, color : &str) -> anyhow::Result<()> {
+        self.commands.push(DrawCommand::Line { from: from, to: to, width: width, color: color.to_owned() });
+        return Ok(());
+    }
+
+    fn text(&mut self, text : &str, center : Vec2, size : f32, color : &str) -> anyhow::Result<()> {
+        self.commands.push(DrawCommand::Text {
+            text: text.to_owned(),
+            center: center,
+            size: size,
+            color: color.to_owned()
+        });
+
+        return Ok(());
+    }
+}
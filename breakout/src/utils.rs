@@ -14,7 +14,7 @@ pub fn set_panic_hook() {
 
 macro_rules! log {
     ( $( $t:tt )* ) => {
-        web_sys::console::log_1(&format!( $( $t )* ).into());
+        web_sys::console::log_1(&format!( $( $t )* ).into())
     }
 }
 
@@ -31,11 +31,30 @@ impl<T> JsValueError<T> for std::result::Result<T, wasm_bindgen::JsValue> {
     }
 }
 
+pub fn local_storage() -> anyhow::Result<web_sys::Storage> {
+    let window = web_sys::window().unwrap();
+
+    return match window.local_storage().to_anyhow()? {
+        Some(storage) => Ok(storage),
+        None => Err(anyhow::anyhow!("Local storage is not available!"))
+    };
+}
+
+pub fn query_parameter(name : &str) -> anyhow::Result<Option<String>> {
+    let location = web_sys::window().unwrap().location();
+    let search = location.search().to_anyhow()?;
+    let params = web_sys::UrlSearchParams::new_with_str(search.as_str()).to_anyhow()?;
+    return Ok(params.get(name));
+}
+
+/// Owns a closure exposed to JavaScript. The event listeners added with `listen` are removed when
+/// the handle is dropped, so the browser never calls a closure that is already freed.
 pub enum ClosureHandle {
     Empty,
     Handle {
         closure : Box<dyn std::any::Any>,
-        js_function : js_sys::Function
+        js_function : js_sys::Function,
+        listeners : Vec<(web_sys::EventTarget, String)>
     }
 }
 
@@ -46,13 +65,14 @@ impl ClosureHandle {
 
         ClosureHandle::Handle {
             closure: Box::new(closure),
-            js_function: js_function
+            js_function: js_function,
+            listeners: Vec::new()
         }
     }
 
     pub fn function(&self) -> &js_sys::Function {
         match self {
-            Self::Handle { closure: _, js_function } => {
+            Self::Handle { closure: _, js_function, listeners: _ } => {
                 return &js_function;
             }
             Self::Empty => {
@@ -60,4 +80,28 @@ impl ClosureHandle {
             }
         }
     }
+
+    /// Adds the closure as the listener of the event on the target until the handle is dropped.
+    pub fn listen(&mut self, target : &web_sys::EventTarget, event_type : &str) -> anyhow::Result<()> {
+        match self {
+            Self::Handle { closure: _, js_function, listeners } => {
+                target.add_event_listener_with_callback(event_type, js_function).to_anyhow()?;
+                listeners.push((target.clone(), event_type.to_owned()));
+                return Ok(());
+            }
+            Self::Empty => {
+                return Err(anyhow::anyhow!("The closure handle is empty."));
+            }
+        }
+    }
+}
+
+impl Drop for ClosureHandle {
+    fn drop(&mut self) {
+        if let Self::Handle { closure: _, js_function, listeners } = self {
+            for (target, event_type) in listeners.iter() {
+                target.remove_event_listener_with_callback(event_type, js_function).ok();
+            }
+        }
+    }
 }
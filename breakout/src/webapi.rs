@@ -1,10 +1,12 @@
-use anyhow::*;
+use anyhow::anyhow;
 use http;
 use serde::de;
 use web_sys;
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
 use apilib::*;
+use gamelib::replay::Replay;
+use gamelib::level::Level;
 
 pub async fn fetch<T: de::DeserializeOwned>(request : http::Request<Option<String>>) -> anyhow::Result<http::Response<T>> {
     let mut opts = web_sys::RequestInit::new();
@@ -151,3 +153,47 @@ pub async fn _list_scores_http(request : &ListScoresRequest) -> anyhow::Result<h
 
     return Ok(response);
 }
+
+pub async fn fetch_replay_http(uri : String) -> anyhow::Result<http::Response<Replay>> {
+    let request = http::Request::builder()
+        .uri(uri)
+        .method("GET")
+        .body(None)?;
+
+    let response : http::Response<Replay> = fetch(request).await?;
+
+    return Ok(response);
+}
+
+pub async fn fetch_replay(uri : String) -> anyhow::Result<Replay> {
+    let response = fetch_replay_http(uri).await?;
+
+    if response.status() != http::status::StatusCode::OK {
+        return Err(anyhow::anyhow!("Failed to fetch a replay."));
+    }
+
+    let result = response.into_body();
+    return Ok(result);
+}
+
+pub async fn fetch_level_http(uri : String) -> anyhow::Result<http::Response<Level>> {
+    let request = http::Request::builder()
+        .uri(uri)
+        .method("GET")
+        .body(None)?;
+
+    let response : http::Response<Level> = fetch(request).await?;
+
+    return Ok(response);
+}
+
+pub async fn fetch_level(uri : String) -> anyhow::Result<Level> {
+    let response = fetch_level_http(uri).await?;
+
+    if response.status() != http::status::StatusCode::OK {
+        return Err(anyhow::anyhow!("Failed to fetch a level."));
+    }
+
+    let result = response.into_body();
+    return Ok(result);
+}
@@ -0,0 +1,111 @@
+use breakout::game::input::*;
+
+#[test]
+fn test_default_bindings() {
+    let bindings = KeyBindings::default();
+
+    assert_eq!(vec![Action::MoveLeft], bindings.actions("ArrowLeft"));
+    assert_eq!(vec![Action::MoveLeft], bindings.actions("KeyA"));
+    assert_eq!(vec![Action::MoveRight], bindings.actions("KeyD"));
+    assert_eq!(vec![Action::Confirm], bindings.actions("Enter"));
+    assert!(bindings.actions("KeyQ").is_empty());
+}
+
+#[test]
+fn test_bind_moves_key_between_actions() {
+    let mut bindings = KeyBindings::default();
+
+    bindings.bind(Action::MoveLeft, "KeyD");
+
+    assert_eq!(vec![Action::MoveLeft], bindings.actions("KeyD"));
+    assert_eq!(&["ArrowLeft", "KeyA", "KeyD"], bindings.keys(Action::MoveLeft));
+    assert_eq!(&["ArrowRight"], bindings.keys(Action::MoveRight));
+
+    bindings.clear(Action::MoveLeft);
+
+    assert!(bindings.keys(Action::MoveLeft).is_empty());
+    assert!(bindings.actions("ArrowLeft").is_empty());
+}
+
+#[test]
+fn test_bindings_json_round_trip() {
+    let mut bindings = KeyBindings::default();
+    bindings.bind(Action::Pause, "KeyQ");
+
+    let json = bindings.to_json().unwrap();
+
+    assert_eq!(bindings, KeyBindings::from_json(json.as_str()).unwrap());
+
+    // The actions missing from the stored bindings keep the defaults.
+    let partial = KeyBindings::from_json(r#"{ "keys": { "Launch": ["KeyL"] } }"#).unwrap();
+
+    assert_eq!(&["KeyL"], partial.keys(Action::Launch));
+    assert_eq!(KeyBindings::default().keys(Action::MoveLeft), partial.keys(Action::MoveLeft));
+}
+
+#[test]
+fn test_action_names() {
+    for (action, name) in ACTIONS.iter() {
+        assert_eq!(Some(*action), parse_action(format!("{:?}", action).as_str()));
+        assert_eq!(*name, action_name(*action));
+    }
+
+    assert_eq!(None, parse_action("Jump"));
+    assert_eq!("A", key_label("KeyA"));
+    assert_eq!("←", key_label("ArrowLeft"));
+}
+
+#[test]
+fn test_touch_drag() {
+    let mut drag = TouchDrag::new();
+
+    assert_eq!(Some(300f32), drag.target(TouchScheme::Drag, Some((1, 300f32)), 100f32));
+    assert_eq!(None, drag.target(TouchScheme::Buttons, Some((1, 300f32)), 100f32));
+    assert_eq!(None, drag.target(TouchScheme::Drag, None, 100f32));
+}
+
+#[test]
+fn test_relative_touch_drag() {
+    let mut drag = TouchDrag::new();
+
+    // The bat moves by as much as the finger, from where it was when the finger went down.
+    assert_eq!(Some(100f32), drag.target(TouchScheme::RelativeDrag, Some((1, 300f32)), 100f32));
+    assert_eq!(Some(150f32), drag.target(TouchScheme::RelativeDrag, Some((1, 350f32)), 120f32));
+    assert_eq!(Some(20f32), drag.target(TouchScheme::RelativeDrag, Some((1, 220f32)), 140f32));
+
+    // A new touch starts from the current position of the bat.
+    assert_eq!(Some(140f32), drag.target(TouchScheme::RelativeDrag, Some((2, 600f32)), 140f32));
+    assert_eq!(None, drag.target(TouchScheme::RelativeDrag, None, 140f32));
+    assert_eq!(Some(90f32), drag.target(TouchScheme::RelativeDrag, Some((2, 10f32)), 90f32));
+}
+
+#[test]
+fn test_touch_schemes_cycle() {
+    let mut scheme = TouchScheme::Drag;
+
+    for _ in 0..TOUCH_SCHEMES.len() {
+        assert!(!scheme.name().is_empty());
+        scheme = scheme.next();
+    }
+
+    assert_eq!(TouchScheme::Drag, scheme);
+    assert_eq!(TouchScheme::RelativeDrag, TouchScheme::Drag.next());
+}
+
+#[test]
+fn test_dead_zone() {
+    assert_eq!(0f32, apply_dead_zone(0.1f32, GAMEPAD_DEAD_ZONE));
+    assert_eq!(0f32, apply_dead_zone(-0.2f32, GAMEPAD_DEAD_ZONE));
+    assert_eq!(1f32, apply_dead_zone(1f32, GAMEPAD_DEAD_ZONE));
+    assert_eq!(-1f32, apply_dead_zone(-1.5f32, GAMEPAD_DEAD_ZONE));
+    assert!((apply_dead_zone(0.6f32, GAMEPAD_DEAD_ZONE) - 0.5f32).abs() < 1e-6f32);
+    assert!((apply_dead_zone(-0.6f32, GAMEPAD_DEAD_ZONE) + 0.5f32).abs() < 1e-6f32);
+}
+
+#[test]
+fn test_gamepad_buttons() {
+    assert_eq!(Some(Action::Confirm), gamepad_action(0));
+    assert_eq!(Some(Action::Pause), gamepad_action(9));
+    assert_eq!(Some(Action::MoveLeft), gamepad_action(14));
+    assert_eq!(None, gamepad_action(5));
+}
@@ -0,0 +1,54 @@
+extern crate nalgebra_glm as glm;
+
+use breakout::game::*;
+use breakout::game::bat::*;
+use gamelib::*;
+use glm::vec2;
+
+const SEED : u64 = 42;
+
+#[test]
+fn test_canvas_to_game() {
+    let game_area = vec2(config::GAME_AREA_WIDTH as f32, config::GAME_AREA_HEIGHT as f32);
+    let canvas_size = game_area * 0.5f32;
+    let (scale, translation) = game_transform(game_area, canvas_size);
+
+    let point = vec2(100f32, 300f32);
+    let canvas_point = point.component_mul(&scale) + translation;
+
+    assert_eq!(vec2(50f32, 150f32), canvas_point);
+    assert_eq!(point, canvas_to_game(canvas_point, game_area, canvas_size));
+}
+
+#[test]
+fn test_steer_bat() {
+    let simulation = Simulation::new(SEED);
+    let bat = &simulation.bat;
+
+    assert_eq!(1f32, steer_bat(bat, bat.position.x + 500f32));
+    assert_eq!(-1f32, steer_bat(bat, bat.position.x - 500f32));
+    assert_eq!(0f32, steer_bat(bat, bat.position.x));
+
+    let near = steer_bat(bat, bat.position.x + 10f32);
+    assert!(0f32 < near && near < 1f32);
+}
+
+#[test]
+fn test_steered_bat_reaches_target() {
+    let mut simulation = Simulation::new(SEED);
+    let target = simulation.bat.position.x - 200f32;
+    let mut last_distance = f32::MAX;
+
+    for _ in 0..100 {
+        let direction = steer_bat(&simulation.bat, target);
+        step(&mut simulation, &Input { direction: direction }).unwrap();
+
+        // The bat doesn't overshoot, it only gets closer.
+        let distance = target - simulation.bat.position.x;
+        assert!(distance <= 0f32);
+        assert!(distance.abs() <= last_distance);
+        last_distance = distance.abs();
+    }
+
+    assert!(last_distance < 1f32);
+}
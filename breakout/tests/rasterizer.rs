@@ -0,0 +1,107 @@
+extern crate nalgebra_glm as glm;
+
+use breakout::game::particles::*;
+use breakout::png::*;
+use breakout::rasterizer::*;
+use breakout::renderer::*;
+use gamelib::*;
+use glm::vec2;
+
+const SEED : u64 = 42;
+const WIDTH : u32 = config::GAME_AREA_WIDTH as u32;
+const HEIGHT : u32 = config::GAME_AREA_HEIGHT as u32;
+
+/// Compares the image with the one stored in `tests/golden`. The stored images are only written
+/// when the `UPDATE_GOLDEN` environment variable is set. A different image is saved to the
+/// temporary directory of the tests, so it can be inspected.
+fn assert_golden(name : &str, image : &Image) {
+    let path = format!("{}/tests/golden/{}.png", env!("CARGO_MANIFEST_DIR"), name);
+    let png = image.to_png();
+
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        std::fs::write(&path, &png).unwrap();
+    }
+
+    if !std::path::Path::new(&path).exists() {
+        panic!("The golden image '{}' is missing, run the tests with UPDATE_GOLDEN=1 to write it.", name);
+    }
+
+    if std::fs::read(&path).unwrap() != png {
+        let actual = format!("{}/{}.png", env!("CARGO_TARGET_TMPDIR"), name);
+        image.save_png(actual.as_str()).unwrap();
+        panic!("The image '{}' has changed, see {}.", name, actual);
+    }
+}
+
+#[test]
+fn test_fill_rect_covers_pixel_centers() {
+    let mut renderer = SoftwareRenderer::new(4, 4);
+    renderer.fill_rect(vec2(0.6f32, 1f32), vec2(2f32, 2f32), "white").unwrap();
+
+    assert_eq!([0, 0, 0, 0], renderer.image.pixel(0, 1));
+    assert_eq!([255, 255, 255, 255], renderer.image.pixel(1, 1));
+    assert_eq!([255, 255, 255, 255], renderer.image.pixel(2, 2));
+    assert_eq!([0, 0, 0, 0], renderer.image.pixel(3, 2));
+    assert_eq!([0, 0, 0, 0], renderer.image.pixel(1, 3));
+}
+
+#[test]
+fn test_transform_and_alpha() {
+    let mut renderer = SoftwareRenderer::new(4, 4);
+    renderer.fill_rect(vec2(0f32, 0f32), vec2(4f32, 4f32), "black").unwrap();
+    renderer.set_transform(vec2(2f32, 2f32), vec2(0f32, 0f32)).unwrap();
+    renderer.set_alpha(0.5f32).unwrap();
+    renderer.fill_rect(vec2(1f32, 1f32), vec2(1f32, 1f32), "white").unwrap();
+
+    assert_eq!([0, 0, 0, 255], renderer.image.pixel(1, 1));
+    assert_eq!([128, 128, 128, 255], renderer.image.pixel(2, 2));
+    assert_eq!([128, 128, 128, 255], renderer.image.pixel(3, 3));
+}
+
+#[test]
+fn test_unknown_color() {
+    let mut renderer = SoftwareRenderer::new(4, 4);
+    assert!(renderer.fill_circle(vec2(2f32, 2f32), 1f32, "chartreuse").is_err());
+}
+
+#[test]
+fn test_png_checksums() {
+    assert_eq!(0xcbf43926, crc32(b"123456789"));
+    assert_eq!(0x11e60398, adler32(b"Wikipedia"));
+
+    let png = encode_png(1, 1, &[255, 0, 0, 255]);
+
+    assert_eq!(&[0x89, b'P', b'N', b'G'], &png[0..4]);
+    // The IEND chunk is always the same.
+    assert_eq!(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82], &png[png.len() - 12..]);
+}
+
+#[test]
+fn test_golden_new_game() {
+    let simulation = Simulation::new(SEED);
+    assert_golden("new_game", &screenshot(&simulation, WIDTH, HEIGHT).unwrap());
+}
+
+#[test]
+fn test_golden_game_in_progress() {
+    let mut simulation = Simulation::new(SEED);
+    let mut particles = Particles::new(SEED, DEFAULT_PARTICLE_BUDGET);
+
+    for _ in 0..300 {
+        let offset = (simulation.balls[0].position.x - simulation.bat.position.x) / 50f32;
+        step(&mut simulation, &Input { direction: offset.max(-1f32).min(1f32) }).unwrap();
+        particles.spawn_events(&simulation);
+        particles.update(config::TIME_STEP as f32);
+    }
+
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    breakout::game::render_simulation(&simulation, &particles, true, &mut renderer, vec2(WIDTH as f32, HEIGHT as f32)).unwrap();
+
+    assert_golden("game_in_progress", &renderer.image);
+}
+
+#[test]
+fn test_golden_thumbnail() {
+    let simulation = Simulation::new(SEED);
+    assert_golden("thumbnail", &screenshot(&simulation, WIDTH / 4, HEIGHT / 4).unwrap());
+}
@@ -0,0 +1,94 @@
+extern crate nalgebra_glm as glm;
+
+use breakout::game::*;
+use breakout::game::particles::*;
+use breakout::renderer::*;
+use gamelib::*;
+use glm::vec2;
+
+const SEED : u64 = 42;
+
+fn canvas_size() -> glm::Vec2 {
+    vec2(config::GAME_AREA_WIDTH as f32, config::GAME_AREA_HEIGHT as f32)
+}
+
+/// Runs the game with the bat following the ball, the way the browser runs it.
+fn play(simulation : &mut Simulation, particles : &mut Particles, seconds : f64) {
+    for _ in 0..(seconds / config::TIME_STEP) as usize {
+        let offset = (simulation.balls[0].position.x - simulation.bat.position.x) / 50f32;
+        step(simulation, &Input { direction: offset.max(-1f32).min(1f32) }).unwrap();
+        particles.spawn_events(simulation);
+        particles.update(config::TIME_STEP as f32);
+    }
+}
+
+fn record(simulation : &Simulation, particles : &Particles, ball_trail : bool) -> RecordingRenderer {
+    let mut renderer = RecordingRenderer::new();
+    render_simulation(simulation, particles, ball_trail, &mut renderer, canvas_size()).unwrap();
+    return renderer;
+}
+
+/// Compares the snapshot with the one stored in `tests/snapshots`. The stored snapshots are only
+/// written when the `UPDATE_SNAPSHOTS` environment variable is set.
+fn assert_snapshot(name : &str, snapshot : &str) {
+    let path = format!("{}/tests/snapshots/{}.txt", env!("CARGO_MANIFEST_DIR"), name);
+
+    if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+        std::fs::write(&path, snapshot).unwrap();
+    }
+
+    if !std::path::Path::new(&path).exists() {
+        panic!("The snapshot '{}' is missing, run the tests with UPDATE_SNAPSHOTS=1 to write it.", name);
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap();
+    assert!(expected == snapshot, "The snapshot '{}' has changed.", name);
+}
+
+#[test]
+fn test_render_new_game() {
+    let simulation = Simulation::new(SEED);
+    let particles = Particles::new(SEED, DEFAULT_PARTICLE_BUDGET);
+
+    assert_snapshot("new_game", record(&simulation, &particles, true).to_snapshot().as_str());
+}
+
+#[test]
+fn test_render_game_in_progress() {
+    let mut simulation = Simulation::new(SEED);
+    let mut particles = Particles::new(SEED, DEFAULT_PARTICLE_BUDGET);
+
+    play(&mut simulation, &mut particles, 3.0);
+
+    assert_snapshot("game_in_progress", record(&simulation, &particles, true).to_snapshot().as_str());
+}
+
+#[test]
+fn test_render_without_ball_trail() {
+    let mut simulation = Simulation::new(SEED);
+    let mut particles = Particles::new(SEED, DEFAULT_PARTICLE_BUDGET);
+
+    play(&mut simulation, &mut particles, 2.0);
+
+    let with_trail = record(&simulation, &particles, true);
+    let without_trail = record(&simulation, &particles, false);
+    let is_line = |command : &&DrawCommand| match command { DrawCommand::Line { .. } => true, _ => false };
+
+    assert!(with_trail.commands.iter().filter(is_line).count() > 0);
+    assert_eq!(0, without_trail.commands.iter().filter(is_line).count());
+}
+
+#[test]
+fn test_render_score_board() {
+    let mut simulation = Simulation::new(SEED);
+    let particles = Particles::new(SEED, DEFAULT_PARTICLE_BUDGET);
+    simulation.stage = GameStage::ScoreBoard;
+
+    let expected = vec![
+        DrawCommand::FillRect { origin: vec2(0f32, 0f32), size: canvas_size(), color: "lightgray".to_owned() },
+        DrawCommand::SetTransform { scale: vec2(1f32, 1f32), translation: vec2(0f32, 0f32) },
+        DrawCommand::ResetTransform
+    ];
+
+    assert_eq!(expected, record(&simulation, &particles, true).commands);
+}
@@ -0,0 +1,126 @@
+use crate::config;
+use crate::random::*;
+
+/// The sounds triggered by the gameplay events.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SoundEffect {
+    BrickHit,
+    BatHit,
+    WallBounce,
+    LifeLost,
+    GameOver
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Triangle,
+    Noise
+}
+
+pub struct SoundDefinition {
+    pub effect : SoundEffect,
+    pub waveform : Waveform,
+    /// The pitch slides from the start to the end frequency, both in hertz.
+    pub start_frequency : f32,
+    pub end_frequency : f32,
+    /// The length of the sound in seconds.
+    pub duration : f32,
+    /// The time it takes the sound to reach the full volume, in seconds.
+    pub attack : f32,
+    pub volume : f32
+}
+
+pub const SOUNDS : [SoundDefinition; 5] = [
+    SoundDefinition {
+        effect: SoundEffect::BrickHit,
+        waveform: Waveform::Square,
+        start_frequency: 880f32,
+        end_frequency: 660f32,
+        duration: 0.08f32,
+        attack: 0.002f32,
+        volume: 0.3f32
+    },
+    SoundDefinition {
+        effect: SoundEffect::BatHit,
+        waveform: Waveform::Triangle,
+        start_frequency: 330f32,
+        end_frequency: 440f32,
+        duration: 0.1f32,
+        attack: 0.002f32,
+        volume: 0.6f32
+    },
+    SoundDefinition {
+        effect: SoundEffect::WallBounce,
+        waveform: Waveform::Sine,
+        start_frequency: 220f32,
+        end_frequency: 180f32,
+        duration: 0.06f32,
+        attack: 0.002f32,
+        volume: 0.5f32
+    },
+    SoundDefinition {
+        effect: SoundEffect::LifeLost,
+        waveform: Waveform::Noise,
+        start_frequency: 2000f32,
+        end_frequency: 200f32,
+        duration: 0.5f32,
+        attack: 0.01f32,
+        volume: 0.5f32
+    },
+    SoundDefinition {
+        effect: SoundEffect::GameOver,
+        waveform: Waveform::Square,
+        start_frequency: 440f32,
+        end_frequency: 110f32,
+        duration: 1.2f32,
+        attack: 0.02f32,
+        volume: 0.4f32
+    }
+];
+
+pub fn sound_definition(effect : SoundEffect) -> &'static SoundDefinition {
+    SOUNDS.iter().find(|sound| sound.effect == effect).unwrap()
+}
+
+/// Synthesizes the mono samples of the sound in the `[-1, 1]` range. The same effect and sample
+/// rate always give the same samples.
+pub fn synthesize(effect : SoundEffect, sample_rate : u32) -> Vec<f32> {
+    let sound = sound_definition(effect);
+    let num_samples = (sound.duration * sample_rate as f32) as usize;
+    let mut random = Random::new(0);
+    let mut samples = Vec::with_capacity(num_samples);
+    let mut phase = 0f32;
+    let mut noise = 0f32;
+
+    for index in 0..num_samples {
+        let time = index as f32 / sample_rate as f32;
+        let progress = time / sound.duration;
+        let frequency = sound.start_frequency + (sound.end_frequency - sound.start_frequency) * progress;
+
+        let attack = if time < sound.attack { time / sound.attack } else { 1f32 };
+        let decay = (1f32 - progress) * (1f32 - progress);
+
+        let previous_phase = phase;
+        phase = (phase + frequency / sample_rate as f32).fract();
+
+        let value = match sound.waveform {
+            Waveform::Sine => libm::sinf(phase * 2f32 * config::PI),
+            Waveform::Square => if phase < 0.5f32 { 1f32 } else { -1f32 },
+            Waveform::Triangle => 1f32 - 4f32 * (phase - 0.5f32).abs(),
+            Waveform::Noise => {
+                // The noise changes once per period, so the frequency sets its pitch.
+                if phase < previous_phase || index == 0 {
+                    noise = random.next_f32() * 2f32 - 1f32;
+                }
+
+                noise
+            }
+        };
+
+        samples.push(value * attack * decay * sound.volume);
+    }
+
+    return samples;
+}
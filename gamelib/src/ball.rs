@@ -0,0 +1,360 @@
+use glm::*;
+use crate::config;
+use crate::config::GameConfig;
+use crate::bat::*;
+use crate::bricks::*;
+use crate::bonus::*;
+use crate::collision::*;
+use crate::audio::*;
+
+fn mul(a: Vec2, b: Vec2) -> Vec2 {
+    vec2(a.x * b.x, a.y * b.y)
+}
+
+// The trigonometric functions from `libm` give the same results in the browser and on the
+// server, which is required to re-simulate submitted games.
+fn rotate(v: Vec2, angle: f32) -> Vec2 {
+    let sin = libm::sinf(angle);
+    let cos = libm::cosf(angle);
+    vec2(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
+
+pub fn reflect(v: Vec2, n: Vec2) -> Vec2 {
+    let v_dot_n = dot(&v, &n);
+    if v_dot_n < 0.0 { v - 2f32 * v_dot_n * n } else { v }
+}
+
+/// The velocity of a ball bounced off the top of the bat. The direction depends on how far from
+/// the center of the bat the ball hit it and on how fast the bat moved, so a bat stopped by the
+/// wall adds no english.
+pub fn bat_rebound_velocity(config : &GameConfig, bat : &Bat, bat_velocity : Vec2, velocity : Vec2, contact : Vec2) -> Vec2 {
+    let offset = ((contact.x - bat.position.x) / (bat.size.x * 0.5f32)).clamp(-1f32, 1f32);
+    let english = (bat_velocity.x / config.bat_velocity).clamp(-1f32, 1f32);
+
+    let max_angle = config.bat_max_rebound_angle;
+    let angle = (offset * max_angle + english * config.bat_english_angle).clamp(-max_angle, max_angle);
+
+    return rotate(vec2(0f32, -1f32), angle) * length(&velocity);
+}
+
+/// Bounces the velocity off a surface moving with `surface_velocity`, keeping the speed. The
+/// velocity is left alone if the ball already moves away from the surface.
+pub fn push_off(velocity : Vec2, normal : Vec2, surface_velocity : Vec2) -> Vec2 {
+    let relative = velocity - surface_velocity;
+
+    if dot(&relative, &normal) >= 0f32 {
+        return velocity;
+    }
+
+    let pushed = reflect(relative, normal) + surface_velocity;
+
+    if pushed == vec2(0f32, 0f32) {
+        return normal * length(&velocity);
+    }
+
+    return normalize(&pushed) * length(&velocity);
+}
+
+pub struct Ball {
+    pub position : Vec2,
+    pub velocity : Vec2,
+    pub size : f32,
+    pub colliding : bool,
+    pub freeze_time : Option<f32>,
+    /// The speed of the last step, with the modifiers applied, zero while the ball is frozen.
+    pub speed : f32,
+    /// The ring buffer of the positions at the ends of the recent steps.
+    trail : Vec<Vec2>,
+    trail_head : usize
+}
+
+impl Ball {
+    pub fn new(config : &GameConfig) -> Ball {
+        let mut ball = Ball {
+            position: vec2(0f32, 0f32),
+            velocity: vec2(0f32, 0f32),
+            size: config.ball_size,
+            colliding: false,
+            freeze_time: None,
+            speed: 0f32,
+            trail: Vec::with_capacity(config::BALL_TRAIL_LENGTH),
+            trail_head: 0
+        };
+
+        ball.reset_position(config, 0.5f32, config.ball_velocity);
+
+        return ball;
+    }
+
+    /// Puts the ball back at the start position. The `random` value from the `[0, 1)` range
+    /// selects the start direction.
+    pub fn reset_position(&mut self, config : &GameConfig, random : f32, velocity : f32) {
+        let ball_start_position = vec2(config::BALL_START_X, config::BALL_START_Y);
+        let ball_start_angle = config::BALL_START_ANGLE * (random - 0.5f32);
+        let ball_start_direction = rotate(vec2(0f32, -1f32), ball_start_angle);
+
+        self.position = mul(ball_start_position, vec2(config.arena_width, config.arena_height));
+        self.velocity = ball_start_direction * velocity;
+
+        self.freeze_time = Some(0f32);
+        self.clear_trail();
+    }
+
+    pub fn clear_trail(&mut self) {
+        self.trail.clear();
+        self.trail_head = 0;
+    }
+
+    /// Remembers the current position, replacing the oldest one when the trail is full.
+    pub fn record_trail(&mut self) {
+        if self.trail.len() < config::BALL_TRAIL_LENGTH {
+            self.trail.push(self.position);
+        }
+        else {
+            self.trail[self.trail_head] = self.position;
+            self.trail_head = (self.trail_head + 1) % config::BALL_TRAIL_LENGTH;
+        }
+    }
+
+    /// The recent positions of the ball from the oldest to the newest.
+    pub fn trail(&self) -> Vec<Vec2> {
+        let (newer, older) = self.trail.split_at(self.trail_head);
+        return older.iter().chain(newer.iter()).cloned().collect();
+    }
+
+    pub fn effective_velocity(&self) -> Vec2 {
+        return self.velocity * match self.freeze_time { Some(_) => 0f32, None => 1f32 };
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImpactKind {
+    Brick,
+    Bat,
+    Wall
+}
+
+/// A place where the ball bounced off an obstacle.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Impact {
+    pub kind : ImpactKind,
+    pub position : Vec2,
+    pub normal : Vec2
+}
+
+pub struct BallStatus {
+    pub brick_hit_count : u32,
+    pub points : i64,
+    pub out_of_arena : bool,
+    pub bonuses : Vec<Bonus>,
+    /// The sounds of the bounces, in the order the ball ran into the obstacles.
+    pub sounds : Vec<SoundEffect>,
+    pub impacts : Vec<Impact>
+}
+
+impl BallStatus {
+    fn new() -> BallStatus {
+        BallStatus { brick_hit_count : 0, points : 0, out_of_arena : false, bonuses : Vec::new(), sounds : Vec::new(), impacts : Vec::new() }
+    }
+}
+
+/// The obstacle the ball ran into.
+enum Obstacle {
+    Brick(usize),
+    Bat,
+    Wall
+}
+
+fn keep_nearest(nearest : &mut Option<(Collision, Obstacle)>, collision : Collision, obstacle : Obstacle) {
+    if nearest.as_ref().is_none_or(|(current, _)| collision.t < current.t) {
+        *nearest = Some((collision, obstacle));
+    }
+}
+
+/// Moves the ball by a single step. The ball bounces off the obstacles in the order it runs into
+/// them, so it can hit several of them during a single step.
+pub fn update_ball(
+    config : &GameConfig,
+    bat : &Bat,
+    ball : &mut Ball,
+    bricks : &mut Bricks,
+    modifiers : &Modifiers,
+    elapsed : f32) -> anyhow::Result<BallStatus> {
+
+    let mut result = BallStatus::new();
+
+    let speed_scale = match ball.freeze_time { Some(_) => 0f32, None => modifiers.ball_speed_scale };
+    ball.speed = length(&ball.velocity) * speed_scale;
+    let bat_velocity = (bat.position - bat.last_position) / elapsed;
+    let game_area = vec2(config.arena_width, config.arena_height);
+
+    // The fraction of the step the ball has already moved through.
+    let mut time = 0f32;
+
+    for _ in 0..config::BALL_MAX_COLLISIONS_PER_STEP {
+        let remaining = elapsed * (1f32 - time);
+        let velocity = ball.velocity * speed_scale;
+        let new_position = ball.position + velocity * remaining;
+        let mut nearest : Option<(Collision, Obstacle)> = None;
+
+        // Only the bricks in the cells swept by the ball are tested.
+        let radius = vec2(ball.size, ball.size);
+        let swept_min = min2(&ball.position, &new_position) - radius;
+        let swept_max = max2(&ball.position, &new_position) + radius;
+
+        for index in bricks.query(swept_min, swept_max) {
+            let brick = &bricks.bricks[index];
+
+            if brick.destruction_time.is_none() {
+                if let Some(collision) = resolve_circle_aabb_collision(
+                    ball.position,
+                    new_position,
+                    ball.size,
+                    brick.position,
+                    brick.size * 0.5) {
+                    keep_nearest(&mut nearest, collision, Obstacle::Brick(index));
+                }
+            }
+        }
+
+        // The ball is swept against the bat in the frame of the bat, so the bat that moves into
+        // the ball is detected as well.
+        let bat_position = mix(&bat.last_position, &bat.position, time);
+
+        if let Some(collision) = resolve_circle_aabb_collision(
+            ball.position + (bat.position - bat_position),
+            new_position,
+            ball.size,
+            bat.position,
+            bat.size * 0.5) {
+            keep_nearest(&mut nearest, collision, Obstacle::Bat);
+        }
+
+        if let Some(collision) = resolve_circle_aabb_inv_collision(
+            ball.position,
+            new_position,
+            ball.size,
+            game_area * 0.5,
+            game_area * 0.5) {
+            if collision.normal != vec2(0f32, -1f32) || modifiers.floor {
+                keep_nearest(&mut nearest, collision, Obstacle::Wall);
+            }
+        }
+
+        let (collision, obstacle) = match nearest {
+            Some(nearest) => nearest,
+            None => {
+                ball.position = new_position;
+                break;
+            }
+        };
+
+        ball.position += velocity * remaining * collision.t;
+        time += (1f32 - time) * collision.t;
+
+        let kind = match obstacle {
+            Obstacle::Brick(_) => ImpactKind::Brick,
+            Obstacle::Bat => ImpactKind::Bat,
+            Obstacle::Wall => ImpactKind::Wall
+        };
+
+        result.impacts.push(Impact { kind: kind, position: collision.point, normal: collision.normal });
+
+        ball.velocity = match obstacle {
+            Obstacle::Brick(index) => {
+                result.brick_hit_count += 1;
+                result.sounds.push(SoundEffect::BrickHit);
+                result.points += bricks.hit(index, &mut result.bonuses);
+                reflect(ball.velocity, collision.normal)
+            },
+            Obstacle::Bat => {
+                result.sounds.push(SoundEffect::BatHit);
+
+                if collision.normal.y < 0f32 {
+                    bat_rebound_velocity(config, bat, bat_velocity, ball.velocity, collision.point)
+                }
+                else {
+                    push_off(ball.velocity, collision.normal, bat_velocity)
+                }
+            },
+            Obstacle::Wall => {
+                result.sounds.push(SoundEffect::WallBounce);
+                reflect(ball.velocity, collision.normal)
+            }
+        };
+    }
+
+    // Neither the sweep nor the bounce may leave the ball inside the bat.
+    if let Some((normal, depth)) = circle_aabb_penetration(ball.position, ball.size, bat.position, bat.size * 0.5) {
+        ball.position += normal * depth;
+        ball.velocity = push_off(ball.velocity, normal, bat_velocity);
+    }
+
+    ball.freeze_time = match ball.freeze_time {
+        Some(time) => if time > config.ball_freeze_time { None } else { Some(time + elapsed) },
+        None => None
+    };
+
+    if ball.position.y - ball.size > game_area.y {
+        result.out_of_arena = true;
+    }
+
+    ball.record_trail();
+
+    return Ok(result);
+}
+
+fn bounce_balls(a : &mut Ball, b : &mut Ball, collision : &Collision, a_old_position : Vec2, b_old_position : Vec2, step_scale : f32) {
+    let normal = collision.normal;
+    let a_contact = mix(&a_old_position, &a.position, collision.t);
+    let b_contact = mix(&b_old_position, &b.position, collision.t);
+    let a_normal_velocity = dot(&a.velocity, &normal);
+    let b_normal_velocity = dot(&b.velocity, &normal);
+
+    if a_normal_velocity - b_normal_velocity < 0f32 {
+        a.velocity += (b_normal_velocity - a_normal_velocity) * normal;
+        b.velocity += (a_normal_velocity - b_normal_velocity) * normal;
+    }
+
+    a.position = a_contact + a.velocity * step_scale * (1f32 - collision.t);
+    b.position = b_contact + b.velocity * step_scale * (1f32 - collision.t);
+
+    let penetration = a.size + b.size - distance(&a.position, &b.position);
+
+    if penetration > 0f32 {
+        a.position += normal * penetration * 0.5f32;
+        b.position -= normal * penetration * 0.5f32;
+    }
+}
+
+/// Bounces the balls that ran into each other during the last step off each other. The balls
+/// have the same mass, so they exchange the velocity components along the contact normal.
+pub fn resolve_ball_collisions(
+    balls : &mut [Ball],
+    old_positions : &[Vec2],
+    modifiers : &Modifiers,
+    elapsed : f32) {
+    let step_scale = elapsed * modifiers.ball_speed_scale;
+
+    for i in 0..balls.len() {
+        for j in (i + 1)..balls.len() {
+            let (head, tail) = balls.split_at_mut(j);
+            let a = &mut head[i];
+            let b = &mut tail[0];
+
+            if a.freeze_time.is_some() || b.freeze_time.is_some() {
+                continue;
+            }
+
+            if let Some(collision) = resolve_circle_circle_collision(
+                old_positions[i],
+                a.position,
+                a.size,
+                old_positions[j],
+                b.position,
+                b.size) {
+                bounce_balls(a, b, &collision, old_positions[i], old_positions[j], step_scale);
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use glm::*;
+use crate::config::GameConfig;
+
+fn mul(a: Vec2, b: Vec2) -> Vec2 {
+    vec2(a.x * b.x, a.y * b.y)
+}
+
+pub fn fmin(a: f32, b: f32) -> f32 { if a < b { a } else { b } }
+pub fn fmax(a: f32, b: f32) -> f32 { if a < b { b } else { a } }
+
+pub struct Bat {
+    pub position : Vec2,
+    /// The position before the last `update_bat`, the balls are swept against the movement.
+    pub last_position : Vec2,
+    pub velocity : Vec2,
+    pub size : Vec2,
+    pub input : Vec2
+}
+
+impl Bat {
+    pub fn new(config : &GameConfig) -> Bat {
+        let bat_position = vec2(config.arena_width * 0.5, config.arena_height - config.bat_offset);
+
+        Bat {
+            position: bat_position,
+            last_position: bat_position,
+            velocity: vec2(config.bat_velocity, config.bat_velocity),
+            size: vec2(config.bat_width, config.bat_height),
+            input: vec2(0.0, 0.0)
+        }
+    }
+}
+
+pub fn update_bat(
+    config : &GameConfig,
+    bat : &mut Bat,
+    elapsed : f32) -> anyhow::Result<()> {
+
+    bat.last_position = bat.position;
+    bat.position += mul(bat.input * elapsed, bat.velocity);
+
+    bat.position.x -= fmin(bat.position.x - bat.size.x * 0.5, 0f32);
+    bat.position.x -= fmax(bat.position.x + bat.size.x * 0.5 - config.arena_width, 0f32);
+
+    return Ok(());
+}
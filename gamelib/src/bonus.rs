@@ -0,0 +1,219 @@
+use glm::*;
+use serde::{Serialize, Deserialize};
+use crate::config;
+use crate::config::GameConfig;
+use crate::bat::*;
+use crate::random::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BonusKind {
+    WidenBat,
+    ShortenBat,
+    DoublePoints,
+    SlowDown,
+    SpeedUp,
+    Blockage,
+    ExtraBall
+}
+
+/// The combined influence of the active effects on the game.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Modifiers {
+    pub bat_width_scale : f32,
+    pub ball_speed_scale : f32,
+    pub score_multiplier : i64,
+    /// The bottom edge of the arena bounces the ball back.
+    pub floor : bool
+}
+
+impl Modifiers {
+    pub const IDENTITY : Modifiers = Modifiers {
+        bat_width_scale: 1f32,
+        ball_speed_scale: 1f32,
+        score_multiplier: 1,
+        floor: false
+    };
+
+    pub fn combine(&self, other : &Modifiers) -> Modifiers {
+        Modifiers {
+            bat_width_scale: self.bat_width_scale * other.bat_width_scale,
+            ball_speed_scale: self.ball_speed_scale * other.ball_speed_scale,
+            score_multiplier: self.score_multiplier * other.score_multiplier,
+            floor: self.floor || other.floor
+        }
+    }
+}
+
+pub struct EffectDefinition {
+    pub kind : BonusKind,
+    /// Zero for the effects that are applied once, when the bonus is caught.
+    pub duration : f32,
+    pub modifiers : Modifiers,
+    pub extra_balls : u32
+}
+
+/// Every bonus that can be found in a brick. A new effect only needs an entry here.
+pub const EFFECTS : [EffectDefinition; 7] = [
+    EffectDefinition {
+        kind: BonusKind::WidenBat,
+        duration: 15f32,
+        modifiers: Modifiers { bat_width_scale: 1.5f32, ..Modifiers::IDENTITY },
+        extra_balls: 0
+    },
+    EffectDefinition {
+        kind: BonusKind::ShortenBat,
+        duration: 10f32,
+        modifiers: Modifiers { bat_width_scale: 0.6f32, ..Modifiers::IDENTITY },
+        extra_balls: 0
+    },
+    EffectDefinition {
+        kind: BonusKind::DoublePoints,
+        duration: 15f32,
+        modifiers: Modifiers { score_multiplier: 2, ..Modifiers::IDENTITY },
+        extra_balls: 0
+    },
+    EffectDefinition {
+        kind: BonusKind::SlowDown,
+        duration: 8f32,
+        modifiers: Modifiers { ball_speed_scale: 0.6f32, ..Modifiers::IDENTITY },
+        extra_balls: 0
+    },
+    EffectDefinition {
+        kind: BonusKind::SpeedUp,
+        duration: 8f32,
+        modifiers: Modifiers { ball_speed_scale: 1.4f32, ..Modifiers::IDENTITY },
+        extra_balls: 0
+    },
+    EffectDefinition {
+        kind: BonusKind::Blockage,
+        duration: 6f32,
+        modifiers: Modifiers { floor: true, ..Modifiers::IDENTITY },
+        extra_balls: 0
+    },
+    EffectDefinition {
+        kind: BonusKind::ExtraBall,
+        duration: 0f32,
+        modifiers: Modifiers::IDENTITY,
+        extra_balls: 1
+    }
+];
+
+pub fn effect_definition(kind : BonusKind) -> &'static EffectDefinition {
+    EFFECTS.iter().find(|effect| effect.kind == kind).unwrap()
+}
+
+/// Decides whether a new brick carries a bonus and which one.
+pub fn random_bonus(random : &mut Random) -> Option<BonusKind> {
+    if random.next_f32() < config::BONUS_PROBABILITY {
+        let index = random.next_u32() as usize % EFFECTS.len();
+        return Some(EFFECTS[index].kind);
+    }
+
+    return None;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ActiveEffect {
+    pub kind : BonusKind,
+    pub remaining : f32
+}
+
+/// Keeps track of the caught bonuses until they expire.
+#[derive(Default)]
+pub struct Effects {
+    pub active : Vec<ActiveEffect>
+}
+
+impl Effects {
+    pub fn new() -> Effects {
+        Effects { active: Vec::new() }
+    }
+
+    /// Activates the effect, catching the same bonus again only restarts its timer.
+    pub fn add(&mut self, kind : BonusKind) {
+        let duration = effect_definition(kind).duration;
+
+        if duration <= 0f32 {
+            return;
+        }
+
+        match self.active.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => effect.remaining = duration,
+            None => self.active.push(ActiveEffect { kind: kind, remaining: duration })
+        }
+    }
+
+    pub fn update(&mut self, elapsed : f32) {
+        for effect in &mut self.active {
+            effect.remaining -= elapsed;
+        }
+
+        self.active.retain(|effect| effect.remaining > 0f32);
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        self.active.iter().fold(Modifiers::IDENTITY, |modifiers, effect| {
+            modifiers.combine(&effect_definition(effect.kind).modifiers)
+        })
+    }
+}
+
+/// A bonus released from a brick. It sways down the arena until it's caught by the bat or
+/// falls out.
+pub struct Bonus {
+    pub kind : BonusKind,
+    pub origin : Vec2,
+    pub position : Vec2,
+    pub size : f32,
+    pub time : f32
+}
+
+impl Bonus {
+    pub fn new(kind : BonusKind, origin : Vec2) -> Bonus {
+        Bonus {
+            kind: kind,
+            origin: origin,
+            position: origin,
+            size: config::BONUS_SIZE,
+            time: 0f32
+        }
+    }
+}
+
+fn bonus_caught(bonus : &Bonus, bat : &Bat) -> bool {
+    let dist = abs(&(bonus.position - bat.position));
+    let min_dist = bat.size * 0.5 + vec2(bonus.size, bonus.size);
+    return dist.x <= min_dist.x && dist.y <= min_dist.y;
+}
+
+/// Moves the bonuses along their trajectories and returns the kinds of the caught ones.
+pub fn update_bonuses(
+    config : &GameConfig,
+    bonuses : &mut Vec<Bonus>,
+    bat : &Bat,
+    elapsed : f32) -> Vec<BonusKind> {
+    let game_area = vec2(config.arena_width, config.arena_height);
+    let mut caught = Vec::new();
+
+    for bonus in bonuses.iter_mut() {
+        bonus.time += elapsed;
+
+        let sway = config::BONUS_SWAY_AMPLITUDE * libm::sinf(bonus.time * config::BONUS_SWAY_FREQUENCY);
+        let x = bonus.origin.x + sway;
+        let x = if x < bonus.size { bonus.size } else if x > game_area.x - bonus.size { game_area.x - bonus.size } else { x };
+
+        bonus.position = vec2(x, bonus.origin.y + config::BONUS_FALLING_VELOCITY * bonus.time);
+
+        if bonus_caught(bonus, bat) {
+            caught.push(bonus.kind);
+        }
+    }
+
+    bonuses.retain(|bonus| !bonus_caught(bonus, bat) && bonus.position.y - bonus.size <= game_area.y);
+
+    return caught;
+}
@@ -0,0 +1,439 @@
+use glm::*;
+use crate::config;
+use crate::config::GameConfig;
+use crate::bonus::*;
+use crate::random::*;
+use crate::layout::*;
+use crate::level::*;
+use serde::{Serialize, Deserialize};
+use std::iter::Iterator;
+use std::cmp::{max};
+
+fn mul(a: Vec2, b: Vec2) -> Vec2 {
+    vec2(a.x * b.x, a.y * b.y)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BrickKind {
+    Normal,
+    /// Needs several hits to be destroyed.
+    Strong,
+    /// Cannot be destroyed, the rows are refilled without waiting for it.
+    Indestructible,
+    /// Destroys the neighbouring bricks when destroyed.
+    Explosive
+}
+
+pub struct BrickDefinition {
+    pub kind : BrickKind,
+    /// Zero for the bricks that cannot be destroyed.
+    pub hit_points : u32,
+    /// The points scored for destroying the brick.
+    pub score : i64,
+    /// The relative frequency of the kind in the randomly filled rows.
+    pub weight : u32,
+    /// The relative frequency once the layout reaches the full difficulty.
+    pub ramp_weight : u32
+}
+
+pub const BRICK_KINDS : [BrickDefinition; 4] = [
+    BrickDefinition { kind: BrickKind::Normal, hit_points: 1, score: 1, weight: 70, ramp_weight: 40 },
+    BrickDefinition { kind: BrickKind::Strong, hit_points: 3, score: 3, weight: 15, ramp_weight: 30 },
+    BrickDefinition { kind: BrickKind::Indestructible, hit_points: 0, score: 0, weight: 5, ramp_weight: 12 },
+    BrickDefinition { kind: BrickKind::Explosive, hit_points: 1, score: 2, weight: 10, ramp_weight: 18 }
+];
+
+pub fn brick_definition(kind : BrickKind) -> &'static BrickDefinition {
+    BRICK_KINDS.iter().find(|brick| brick.kind == kind).unwrap()
+}
+
+fn brick_weight(brick : &BrickDefinition, difficulty : f32) -> u32 {
+    let weight = brick.weight as f32 + (brick.ramp_weight as f32 - brick.weight as f32) * difficulty;
+    return weight.round() as u32;
+}
+
+/// Picks a kind of brick, the `difficulty` from the `[0, 1]` range moves the frequencies from
+/// `weight` towards `ramp_weight`.
+pub fn random_brick_kind(random : &mut Random, difficulty : f32) -> BrickKind {
+    let total : u32 = BRICK_KINDS.iter().map(|brick| brick_weight(brick, difficulty)).sum();
+    let mut value = random.next_u32() % total;
+
+    for brick in BRICK_KINDS.iter() {
+        let weight = brick_weight(brick, difficulty);
+
+        if value < weight {
+            return brick.kind;
+        }
+
+        value -= weight;
+    }
+
+    return BrickKind::Normal;
+}
+
+#[derive(Debug, Clone)]
+pub struct Brick {
+    pub position : Vec2,
+    pub size : Vec2,
+    pub destruction_time : Option<f32>,
+    pub bonus : Option<BonusKind>,
+    pub kind : BrickKind,
+    pub hit_points : u32
+}
+
+impl Brick {
+    pub fn new(
+        position : Vec2,
+        size : Vec2) -> Brick {
+        Brick {
+            position: position,
+            size: size,
+            destruction_time: None,
+            bonus: None,
+            kind: BrickKind::Normal,
+            hit_points: 1
+        }
+    }
+
+    pub fn set_kind(&mut self, kind : BrickKind) {
+        self.kind = kind;
+        self.hit_points = brick_definition(kind).hit_points;
+    }
+
+    pub fn indestructible(&self) -> bool {
+        self.kind == BrickKind::Indestructible
+    }
+
+    /// Fills the brick with a generated cell, an empty cell is a brick that is already destroyed.
+    pub fn set_cell(&mut self, cell : Option<Cell>) {
+        match cell {
+            Some(cell) => {
+                self.set_kind(cell.kind);
+                self.bonus = cell.bonus;
+                self.destruction_time = None;
+            },
+            None => {
+                self.set_kind(BrickKind::Normal);
+                self.hit_points = 0;
+                self.bonus = None;
+                self.destruction_time = Some(config::BRICK_DESTRUCTION_TIME);
+            }
+        }
+    }
+}
+
+pub struct BrickConfig {
+    pub origin : Vec2,
+    pub size : Vec2,
+    pub width : f32,
+    pub height : f32,
+    pub col_width : f32,
+    pub row_height : f32,
+    pub spacing : Vec2
+}
+
+impl BrickConfig {
+    pub fn new(config : &GameConfig, bricks_cols : u32) -> BrickConfig {
+        let game_area = vec2(config.arena_width, config.arena_height);
+
+        let brick_spacing = config.brick_spacing;
+        let brick_width = ((game_area.x - brick_spacing) / bricks_cols as f32 - brick_spacing).floor();
+        let brick_height = (brick_width * 0.5f32).floor();
+
+        return BrickConfig {
+            origin: vec2(brick_width, brick_height) * 0.5f32,
+            size: vec2(brick_width, brick_height),
+            width: brick_width,
+            height: brick_height,
+            col_width: brick_width + brick_spacing,
+            row_height: brick_height + brick_spacing,
+            spacing: vec2(brick_spacing, brick_spacing)
+        };
+    }
+
+    pub fn grid_position(&self, x : u32, y : u32) -> Vec2 {
+        let index = vec2(x as f32, y as f32);
+        return mul(self.size + self.spacing, index) + self.origin + self.spacing;
+    }
+}
+
+pub struct Bricks {
+    pub bricks : Vec<Brick>,
+    pub origin : Vec2,
+    pub num_cols : u32,
+    pub num_rows : u32,
+    pub row_shift : u32,
+    pub brick_config : BrickConfig,
+    /// Generates the new rows, `None` for the levels that are not refilled.
+    pub layout : Option<LayoutGenerator>,
+    /// The number of rows generated so far, including the initial grid.
+    pub num_generated_rows : u64,
+    pub falling_velocity : f32,
+    /// Copies of the bricks destroyed by the hits, the simulation clears it on every step. The
+    /// copies stay valid when the row of the brick is refilled.
+    pub destroyed : Vec<Brick>
+}
+
+impl Bricks {
+    /// Creates the endless grid generated from the `seed`.
+    pub fn new(config : &GameConfig, seed : u64) -> Bricks {
+        let num_cols = config.num_brick_cols;
+        let num_rows = config.num_brick_rows;
+        let layout = LayoutGenerator::new(seed, num_cols);
+
+        // The bottom row is generated first, the rows above it are the later ones.
+        let cells = (0..num_rows).flat_map(|y| layout.row((num_rows - 1 - y) as u64)).collect();
+
+        return Bricks::with_cells(config, num_cols, num_rows, cells, Some(layout));
+    }
+
+    /// Creates the bricks of a hand-made level, its rows are not refilled.
+    /// Fails if the level is invalid.
+    pub fn from_level(config : &GameConfig, level : &Level) -> anyhow::Result<Bricks> {
+        let cells = level.cells()?;
+
+        if cells.len() != level.num_cells()? {
+            return Err(anyhow::anyhow!("The level '{}' has a wrong number of cells.", level.name));
+        }
+
+        return Ok(Bricks::with_cells(config, level.num_cols, level.num_rows, cells, None));
+    }
+
+    fn with_cells(
+        config : &GameConfig,
+        num_cols : u32,
+        num_rows : u32,
+        cells : Vec<Option<Cell>>,
+        layout : Option<LayoutGenerator>) -> Bricks {
+        let mut bricks : Vec<Brick> = vec![];
+
+        let brick_config = BrickConfig::new(config, num_cols);
+
+        let brick_size = vec2(
+            brick_config.width,
+            brick_config.height);
+
+        let brick_origin = brick_size * 0.5;
+
+        for y in 0..num_rows {
+            for x in 0..num_cols {
+                let index = vec2(x as f32, y as f32);
+
+                let mut brick = Brick::new(
+                    brick_config.spacing + mul(brick_size + brick_config.spacing, index) + brick_origin,
+                    brick_size
+                );
+
+                brick.set_cell(cells[(y * num_cols + x) as usize]);
+
+                bricks.push(brick);
+            }
+        }
+
+        return Bricks {
+            bricks: bricks,
+            origin: vec2(0f32, 0f32),
+            num_cols: num_cols,
+            num_rows: num_rows,
+            row_shift: 0,
+            brick_config: brick_config,
+            layout: layout,
+            num_generated_rows: num_rows as u64,
+            falling_velocity: config.bricks_falling_velocity,
+            destroyed: Vec::new()
+        };
+    }
+
+    pub fn last_row(&self) -> u32 {
+        (self.row_shift + self.num_rows - 1) % self.num_rows
+    }
+
+    pub fn last_row_empty(&self) -> bool {
+        let last_row = self.last_row();
+        let begin = (last_row * self.num_cols) as usize;
+        let end = begin + self.num_cols as usize;
+        return self.bricks[begin..end].iter().all(|brick| brick.destruction_time.is_some() || brick.indestructible());
+    }
+
+    /// The row of the brick on the screen, counted from the top.
+    fn screen_row(&self, index : usize) -> u32 {
+        let storage_row = index as u32 / self.num_cols;
+        return (storage_row + self.num_rows - self.row_shift) % self.num_rows;
+    }
+
+    /// The position of the center of the cell the brick belongs to.
+    pub fn cell_position(&self, index : usize) -> Vec2 {
+        let col = index as u32 % self.num_cols;
+        return self.origin + self.brick_config.grid_position(col, self.screen_row(index));
+    }
+
+    /// Indices of the bricks whose cells overlap the rectangle from `min` to `max`, row by row
+    /// from the top. The cells follow the grid as it scrolls with `origin`.
+    pub fn query(&self, min : Vec2, max : Vec2) -> Vec<usize> {
+        // The slack covers the rounding errors of the falling bricks.
+        let slack = self.brick_config.spacing * 0.5f32;
+        let local_min = min - slack - self.origin - self.brick_config.spacing;
+        let local_max = max + slack - self.origin - self.brick_config.spacing;
+
+        if local_max.x < 0f32 || local_max.y < 0f32 {
+            return Vec::new();
+        }
+
+        let cell = |value : f32, size : f32, count : u32| {
+            let cell = (value / size).floor();
+            if cell < 0f32 { 0 } else if cell >= count as f32 { count } else { cell as u32 }
+        };
+
+        let col_begin = cell(local_min.x, self.brick_config.col_width, self.num_cols);
+        let col_end = cell(local_max.x, self.brick_config.col_width, self.num_cols - 1) + 1;
+        let row_begin = cell(local_min.y, self.brick_config.row_height, self.num_rows);
+        let row_end = cell(local_max.y, self.brick_config.row_height, self.num_rows - 1) + 1;
+
+        let mut result = Vec::new();
+
+        for row in row_begin..row_end {
+            let storage_row = (row + self.row_shift) % self.num_rows;
+
+            for col in col_begin..col_end {
+                result.push((storage_row * self.num_cols + col) as usize);
+            }
+        }
+
+        return result;
+    }
+
+    /// The number of times the last row was cleared and refilled.
+    pub fn num_refilled_rows(&self) -> u32 {
+        return (self.num_generated_rows - self.num_rows as u64) as u32;
+    }
+
+    /// Every brick that can be destroyed is destroyed.
+    pub fn cleared(&self) -> bool {
+        return self.bricks.iter().all(|brick| brick.destruction_time.is_some() || brick.indestructible());
+    }
+
+    /// Indices of the bricks adjacent to the brick on the screen, including the diagonal ones.
+    pub fn neighbours(&self, index : usize) -> Vec<usize> {
+        let num_cols = self.num_cols as i32;
+        let num_rows = self.num_rows as i32;
+        let col = index as i32 % num_cols;
+        let row = (index as i32 / num_cols - self.row_shift as i32 + num_rows) % num_rows;
+        let mut result = Vec::new();
+
+        for y in (row - 1)..(row + 2) {
+            for x in (col - 1)..(col + 2) {
+                if (x, y) != (col, row) && 0 <= x && x < num_cols && 0 <= y && y < num_rows {
+                    let storage_row = (y + self.row_shift as i32) % num_rows;
+                    result.push((storage_row * num_cols + x) as usize);
+                }
+            }
+        }
+
+        return result;
+    }
+
+    /// Applies a ball hit to the brick. Returns the points scored for the destroyed bricks and
+    /// puts the bonuses they carried to `released`.
+    pub fn hit(&mut self, index : usize, released : &mut Vec<Bonus>) -> i64 {
+        let brick = &mut self.bricks[index];
+
+        if brick.destruction_time.is_some() || brick.indestructible() {
+            return 0;
+        }
+
+        brick.hit_points = max(brick.hit_points, 1) - 1;
+
+        if brick.hit_points != 0 {
+            return 0;
+        }
+
+        let mut points = 0;
+        let mut pending = vec![index];
+
+        while let Some(index) = pending.pop() {
+            let brick = &mut self.bricks[index];
+
+            if brick.destruction_time.is_some() || brick.indestructible() {
+                continue;
+            }
+
+            brick.destruction_time = Some(0f32);
+            brick.hit_points = 0;
+            points += brick_definition(brick.kind).score;
+            self.destroyed.push(brick.clone());
+
+            if let Some(kind) = brick.bonus.take() {
+                released.push(Bonus::new(kind, brick.position));
+            }
+
+            if brick.kind == BrickKind::Explosive {
+                pending.extend(self.neighbours(index));
+            }
+        }
+
+        return points;
+    }
+
+    /// Moves the last row to the top and fills it with the next generated row.
+    pub fn reset_last_row(&mut self) {
+        let cells = match &self.layout {
+            Some(layout) => layout.row(self.num_generated_rows),
+            None => return
+        };
+
+        self.row_shift = self.last_row();
+        self.origin += vec2(0f32, -self.brick_config.row_height);
+        let begin = (self.row_shift * self.num_cols) as usize;
+        let end = begin + self.num_cols as usize;
+        self.num_generated_rows += 1;
+
+        for (i, brick) in self.bricks[begin..end].iter_mut().enumerate() {
+            brick.position = self.origin + self.brick_config.grid_position(i as u32, 0);
+            brick.set_cell(cells[i]);
+        }
+    }
+
+    pub fn update(&mut self, elapsed : f32) -> anyhow::Result<()> {
+
+        let mut should_fall = false;
+        let mut should_reset = false;
+        let mut offset = 0f32;
+
+        if self.origin.y != 0f32 {
+            offset = self.falling_velocity * elapsed;
+            should_fall = true;
+
+            if self.origin.y + offset >= 0f32 {
+                self.origin.y = 0f32;
+                should_reset = true;
+            }
+            else {
+                self.origin.y += offset;
+            }
+        }
+
+        for brick in &mut self.bricks {
+            if let Some(destruction_time) = brick.destruction_time {
+                brick.destruction_time = Some(destruction_time + elapsed);
+            }
+
+            if should_fall && !should_reset {
+                brick.position.y += offset;
+            }
+        }
+
+        // The bricks that stopped falling are put exactly in their cells, so the rounding errors
+        // of the movement do not add up.
+        if should_reset {
+            for index in 0..self.bricks.len() {
+                self.bricks[index].position = self.cell_position(index);
+            }
+        }
+
+        if self.layout.is_some() && self.last_row_empty() {
+            self.reset_last_row();
+        }
+
+        return Ok(());
+    }
+}
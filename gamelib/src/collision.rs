@@ -0,0 +1,280 @@
+use glm::*;
+
+#[derive(Copy, Clone)]
+pub struct Collision {
+    pub point : Vec2,
+    pub normal : Vec2,
+    pub t : f32
+}
+
+/// The face of the box nearest to a point inside of it, as the outward normal and the distance
+/// to the face.
+fn nearest_face(point : Vec2, aabb_origin : Vec2, aabb_radius : Vec2) -> (Vec2, f32) {
+    let offset = point - aabb_origin;
+    let x_distance = aabb_radius.x - offset.x.abs();
+    let y_distance = aabb_radius.y - offset.y.abs();
+
+    if x_distance < y_distance {
+        return (vec2(offset.x.signum(), 0f32), x_distance);
+    }
+    else {
+        return (vec2(0f32, offset.y.signum()), y_distance);
+    }
+}
+
+/// The direction and the depth to push the circle out of the box, `None` if they don't overlap.
+pub fn circle_aabb_penetration(
+    circle_origin : Vec2,
+    circle_radius : f32,
+    aabb_origin : Vec2,
+    aabb_radius : Vec2) -> Option<(Vec2, f32)> {
+    let closest = aabb_origin + clamp_vec(&(circle_origin - aabb_origin), &-aabb_radius, &aabb_radius);
+    let outside = circle_origin - closest;
+
+    if outside != vec2(0f32, 0f32) {
+        let distance = length(&outside);
+
+        if distance >= circle_radius {
+            return None;
+        }
+
+        return Some((outside / distance, circle_radius - distance));
+    }
+
+    let (normal, distance) = nearest_face(circle_origin, aabb_origin, aabb_radius);
+    return Some((normal, distance + circle_radius));
+}
+
+/// The time at which a point moving along `motion` enters the box and the normal of the face it
+/// enters through.
+fn sweep_point_aabb(origin : Vec2, motion : Vec2, aabb_origin : Vec2, aabb_radius : Vec2) -> Option<(f32, Vec2)> {
+    let mut t_enter = f32::NEG_INFINITY;
+    let mut t_exit = f32::INFINITY;
+    let mut normal = vec2(0f32, 0f32);
+
+    for axis in 0..2 {
+        let min = aabb_origin[axis] - aabb_radius[axis];
+        let max = aabb_origin[axis] + aabb_radius[axis];
+
+        if motion[axis] == 0f32 {
+            if origin[axis] <= min || max <= origin[axis] {
+                return None;
+            }
+
+            continue;
+        }
+
+        let (near, far, side) = if motion[axis] > 0f32 {
+            ((min - origin[axis]) / motion[axis], (max - origin[axis]) / motion[axis], -1f32)
+        }
+        else {
+            ((max - origin[axis]) / motion[axis], (min - origin[axis]) / motion[axis], 1f32)
+        };
+
+        if near > t_enter {
+            t_enter = near;
+            normal = vec2(0f32, 0f32);
+            normal[axis] = side;
+        }
+
+        if far < t_exit {
+            t_exit = far;
+        }
+    }
+
+    if t_enter > t_exit || !(0f32..=1f32).contains(&t_enter) {
+        return None;
+    }
+
+    return Some((t_enter, normal));
+}
+
+/// The time at which a point moving along `motion` enters the circle.
+fn sweep_point_circle(origin : Vec2, motion : Vec2, center : Vec2, radius : f32) -> Option<f32> {
+    let offset = origin - center;
+    let a = dot(&motion, &motion);
+    let b = dot(&offset, &motion);
+    let c = dot(&offset, &offset) - radius * radius;
+
+    if a == 0f32 || b >= 0f32 {
+        return None;
+    }
+
+    let discriminant = b * b - a * c;
+
+    if discriminant < 0f32 {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / a;
+
+    if !(0f32..=1f32).contains(&t) {
+        return None;
+    }
+
+    return Some(t);
+}
+
+/// Swept test of a circle against a static box. The circle hits the box when its center enters
+/// the box rounded by the radius of the circle, which is the union of the box stretched
+/// horizontally, the box stretched vertically and the circles around the corners. A circle that
+/// already overlaps the box collides at `t = 0` unless it moves out of the box.
+pub fn resolve_circle_aabb_collision(
+    circle_old_origin : Vec2,
+    circle_new_origin : Vec2,
+    circle_radius : f32,
+    aabb_origin : Vec2,
+    aabb_radius : Vec2) -> Option<Collision> {
+    let motion = circle_new_origin - circle_old_origin;
+
+    let reach = aabb_radius + vec2(circle_radius, circle_radius);
+    let swept_min = min2(&circle_old_origin, &circle_new_origin);
+    let swept_max = max2(&circle_old_origin, &circle_new_origin);
+
+    if swept_max.x < aabb_origin.x - reach.x || aabb_origin.x + reach.x < swept_min.x ||
+        swept_max.y < aabb_origin.y - reach.y || aabb_origin.y + reach.y < swept_min.y {
+        return None;
+    }
+
+    if let Some((normal, _)) = circle_aabb_penetration(circle_old_origin, circle_radius, aabb_origin, aabb_radius) {
+        if dot(&motion, &normal) >= 0f32 {
+            return None;
+        }
+
+        return Some(Collision {
+            point: circle_old_origin - normal * circle_radius,
+            normal: normal,
+            t: 0f32
+        });
+    }
+
+    let mut result : Option<(f32, Vec2)> = None;
+
+    let mut closer = |t : f32, normal : Vec2| {
+        if result.is_none_or(|(best, _)| t < best) {
+            result = Some((t, normal));
+        }
+    };
+
+    let stretched = [
+        aabb_radius + vec2(circle_radius, 0f32),
+        aabb_radius + vec2(0f32, circle_radius)];
+
+    for radius in stretched.iter() {
+        if let Some((t, normal)) = sweep_point_aabb(circle_old_origin, motion, aabb_origin, *radius) {
+            closer(t, normal);
+        }
+    }
+
+    for corner in [vec2(-1f32, -1f32), vec2(1f32, -1f32), vec2(-1f32, 1f32), vec2(1f32, 1f32)].iter() {
+        let center = aabb_origin + aabb_radius.component_mul(corner);
+
+        if let Some(t) = sweep_point_circle(circle_old_origin, motion, center, circle_radius) {
+            closer(t, normalize(&(circle_old_origin + motion * t - center)));
+        }
+    }
+
+    return result.map(|(t, normal)| {
+        let origin = circle_old_origin + motion * t;
+
+        Collision {
+            point: origin - normal * circle_radius,
+            normal: normal,
+            t: t
+        }
+    });
+}
+
+/// Swept test of a circle that has to stay inside of a box. The normal points inside the box.
+pub fn resolve_circle_aabb_inv_collision(
+    circle_old_origin : Vec2,
+    circle_new_origin : Vec2,
+    circle_radius : f32,
+    aabb_origin : Vec2,
+    aabb_radius : Vec2) -> Option<Collision> {
+    let motion = circle_new_origin - circle_old_origin;
+    let inner_radius = aabb_radius - vec2(circle_radius, circle_radius);
+    let mut result : Option<(f32, Vec2)> = None;
+
+    for axis in 0..2 {
+        if motion[axis] == 0f32 {
+            continue;
+        }
+
+        let side = if motion[axis] > 0f32 { 1f32 } else { -1f32 };
+        let wall = aabb_origin[axis] + inner_radius[axis] * side;
+        let t = (wall - circle_old_origin[axis]) / motion[axis];
+        let t = if t < 0f32 { 0f32 } else { t };
+
+        if t <= 1f32 && result.is_none_or(|(best, _)| t < best) {
+            let mut normal = vec2(0f32, 0f32);
+            normal[axis] = -side;
+            result = Some((t, normal));
+        }
+    }
+
+    return result.map(|(t, normal)| {
+        let origin = circle_old_origin + motion * t;
+
+        Collision {
+            point: origin - normal * circle_radius,
+            normal: normal,
+            t: t
+        }
+    });
+}
+
+/// Swept test of two moving circles. The normal points from the second circle towards the first
+/// one and `t` is the fraction of the step at which they touch.
+pub fn resolve_circle_circle_collision(
+    a_old_origin : Vec2,
+    a_new_origin : Vec2,
+    a_radius : f32,
+    b_old_origin : Vec2,
+    b_new_origin : Vec2,
+    b_radius : f32) -> Option<Collision> {
+    let radius = a_radius + b_radius;
+    let offset = a_old_origin - b_old_origin;
+    let motion = (a_new_origin - a_old_origin) - (b_new_origin - b_old_origin);
+
+    let c = dot(&offset, &offset) - radius * radius;
+
+    if c < 0f32 {
+        let normal = if offset != vec2(0f32, 0f32) { normalize(&offset) } else { vec2(0f32, -1f32) };
+
+        return Some(Collision {
+            point: b_old_origin + normal * b_radius,
+            normal: normal,
+            t: 0f32
+        });
+    }
+
+    let a = dot(&motion, &motion);
+    let b = dot(&offset, &motion);
+
+    if a == 0f32 || b >= 0f32 {
+        return None;
+    }
+
+    let discriminant = b * b - a * c;
+
+    if discriminant < 0f32 {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / a;
+
+    if t > 1f32 {
+        return None;
+    }
+
+    let a_origin = mix(&a_old_origin, &a_new_origin, t);
+    let b_origin = mix(&b_old_origin, &b_new_origin, t);
+    let normal = normalize(&(a_origin - b_origin));
+
+    return Some(Collision {
+        point: b_origin + normal * b_radius,
+        normal: normal,
+        t: t
+    });
+}
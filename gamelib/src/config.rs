@@ -0,0 +1,202 @@
+use serde::{Serialize, Deserialize};
+
+// Not `std::f32::consts::PI`, the recorded replays depend on the exact value.
+#[allow(clippy::approx_constant)]
+pub const PI : f32 = 3.141592;
+
+pub const GAME_AREA_WIDTH : f64 = 726f64;
+pub const GAME_AREA_HEIGHT : f64 = 968f64;
+
+pub const BALL_VELOCITY : f32 = 1000f32;
+pub const BALL_START_SIZE : f32 = 19f32;
+pub const BALL_START_X : f32 = 0.5f32;
+pub const BALL_START_Y : f32 = 0.75f32;
+pub const BALL_START_ANGLE : f32 = PI * 0.7f32;
+pub const BALL_FREEZE_TIME : f32 = 1f32;
+pub const BALL_MAX_COLLISIONS_PER_STEP : u32 = 8;
+/// The number of the recent positions of the ball kept for drawing its trail.
+pub const BALL_TRAIL_LENGTH : usize = 8;
+
+pub const BAT_WIDTH : f32 = 200f32;
+pub const BAT_HEIGHT : f32 = 20f32;
+pub const BAT_VELOCITY : f32 = 1000f32;
+/// The distance between the center of the bat and the bottom of the arena.
+pub const BAT_OFFSET : f32 = 100f32;
+/// The largest angle between the vertical and the direction of a ball bounced off the bat.
+pub const BAT_MAX_REBOUND_ANGLE : f32 = PI * 0.35f32;
+/// The angle added to the rebound direction when the bat moves at full speed.
+pub const BAT_ENGLISH_ANGLE : f32 = PI * 0.1f32;
+
+pub const BRICKS_FALLING_VELOCITY : f32 = 91f32;
+pub const NUM_BRICK_COLS : u32 = 10;
+pub const NUM_BRICK_ROWS : u32 = 10;
+pub const BRICK_SPACING : f32 = 10f32;
+pub const BRICK_DESTRUCTION_TIME : f32 = 1f32;
+/// The largest grid of bricks, the levels come from the URLs and cannot ask for a huge allocation.
+pub const MAX_GRID_COLS : u32 = 64;
+pub const MAX_GRID_ROWS : u32 = 64;
+
+pub const LAYOUT_RAMP_ROWS : u64 = 50;
+pub const LAYOUT_GAP_PROBABILITY : f32 = 0.3f32;
+
+pub const BONUS_PROBABILITY : f32 = 0.1f32;
+pub const BONUS_SIZE : f32 = 12f32;
+pub const BONUS_FALLING_VELOCITY : f32 = 200f32;
+pub const BONUS_SWAY_AMPLITUDE : f32 = 40f32;
+pub const BONUS_SWAY_FREQUENCY : f32 = 4f32;
+
+pub const GAME_OVER_PAUSE_TIME : f64 = 2f64;
+
+pub const TIME_STEP : f64 = 0.01f64;
+pub const NUM_LIVES : u32 = 3;
+pub const CHAOS_NUM_BALLS : u32 = 3;
+
+/// The rules of the game that can be tuned without a rebuild. The defaults are the constants
+/// above, a JSON object overrides only the fields it contains.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    pub arena_width : f32,
+    pub arena_height : f32,
+    pub ball_velocity : f32,
+    pub ball_size : f32,
+    pub ball_freeze_time : f32,
+    pub bat_width : f32,
+    pub bat_height : f32,
+    pub bat_velocity : f32,
+    pub bat_offset : f32,
+    pub bat_max_rebound_angle : f32,
+    pub bat_english_angle : f32,
+    pub num_brick_cols : u32,
+    pub num_brick_rows : u32,
+    pub brick_spacing : f32,
+    pub bricks_falling_velocity : f32,
+    pub num_lives : u32
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig {
+            arena_width: GAME_AREA_WIDTH as f32,
+            arena_height: GAME_AREA_HEIGHT as f32,
+            ball_velocity: BALL_VELOCITY,
+            ball_size: BALL_START_SIZE,
+            ball_freeze_time: BALL_FREEZE_TIME,
+            bat_width: BAT_WIDTH,
+            bat_height: BAT_HEIGHT,
+            bat_velocity: BAT_VELOCITY,
+            bat_offset: BAT_OFFSET,
+            bat_max_rebound_angle: BAT_MAX_REBOUND_ANGLE,
+            bat_english_angle: BAT_ENGLISH_ANGLE,
+            num_brick_cols: NUM_BRICK_COLS,
+            num_brick_rows: NUM_BRICK_ROWS,
+            brick_spacing: BRICK_SPACING,
+            bricks_falling_velocity: BRICKS_FALLING_VELOCITY,
+            num_lives: NUM_LIVES
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let positive = [
+            ("arena_width", self.arena_width),
+            ("arena_height", self.arena_height),
+            ("ball_velocity", self.ball_velocity),
+            ("ball_size", self.ball_size),
+            ("bat_width", self.bat_width),
+            ("bat_height", self.bat_height),
+            ("bat_velocity", self.bat_velocity),
+            ("bricks_falling_velocity", self.bricks_falling_velocity)
+        ];
+
+        for (name, value) in positive.iter() {
+            if !value.is_finite() || *value <= 0f32 {
+                return Err(anyhow::anyhow!("The '{}' has to be positive.", name));
+            }
+        }
+
+        if !self.ball_freeze_time.is_finite() || self.ball_freeze_time < 0f32 {
+            return Err(anyhow::anyhow!("The 'ball_freeze_time' cannot be negative."));
+        }
+
+        if !self.bat_max_rebound_angle.is_finite() || self.bat_max_rebound_angle <= 0f32 || self.bat_max_rebound_angle >= PI * 0.5f32 {
+            return Err(anyhow::anyhow!("The 'bat_max_rebound_angle' has to be between zero and a right angle."));
+        }
+
+        if !self.bat_english_angle.is_finite() || self.bat_english_angle < 0f32 || self.bat_english_angle > self.bat_max_rebound_angle {
+            return Err(anyhow::anyhow!("The 'bat_english_angle' has to be between zero and the 'bat_max_rebound_angle'."));
+        }
+
+        if self.num_lives == 0 {
+            return Err(anyhow::anyhow!("The 'num_lives' cannot be zero."));
+        }
+
+        if self.bat_width >= self.arena_width || !self.bat_offset.is_finite() || self.bat_offset <= 0f32 || self.bat_offset >= self.arena_height {
+            return Err(anyhow::anyhow!("The bat doesn't fit in the arena."));
+        }
+
+        return self.validate_grid(self.num_brick_cols, self.num_brick_rows);
+    }
+
+    /// Checks that the grid of bricks isn't too large and that the bricks of the grid aren't too
+    /// small to be drawn and hit. The levels bring their own grids.
+    pub fn validate_grid(&self, num_cols : u32, num_rows : u32) -> anyhow::Result<()> {
+        if num_cols == 0 || num_rows == 0 {
+            return Err(anyhow::anyhow!("The grid of bricks cannot be empty."));
+        }
+
+        if num_cols > MAX_GRID_COLS || num_rows > MAX_GRID_ROWS || num_cols.checked_mul(num_rows).is_none() {
+            return Err(anyhow::anyhow!("The grid of bricks is larger than {}x{} cells.", MAX_GRID_COLS, MAX_GRID_ROWS));
+        }
+
+        if !self.brick_spacing.is_finite() || self.brick_spacing < 0f32 || self.brick_spacing >= self.arena_width {
+            return Err(anyhow::anyhow!("The 'brick_spacing' has to be between zero and the width of the arena."));
+        }
+
+        let brick_config = crate::bricks::BrickConfig::new(self, num_cols);
+
+        if brick_config.width <= 0f32 || brick_config.height <= 0f32 {
+            return Err(anyhow::anyhow!("The bricks of a grid with {} columns don't fit in the arena.", num_cols));
+        }
+
+        return Ok(());
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        return Ok(serde_json::to_string(self)?);
+    }
+
+    pub fn from_json(json : &str) -> anyhow::Result<GameConfig> {
+        let config : GameConfig = serde_json::from_str(json)?;
+        config.validate()?;
+        return Ok(config);
+    }
+
+    /// The names of the fields, the same as in the JSON form.
+    pub fn field_names() -> Vec<String> {
+        match serde_json::to_value(GameConfig::default()) {
+            Ok(serde_json::Value::Object(fields)) => fields.keys().cloned().collect(),
+            _ => Vec::new()
+        }
+    }
+
+    /// Returns the config with the field changed to the `value` written as JSON, for example
+    /// taken from a query parameter.
+    pub fn with_field(&self, name : &str, value : &str) -> anyhow::Result<GameConfig> {
+        let mut fields = match serde_json::to_value(self)? {
+            serde_json::Value::Object(fields) => fields,
+            _ => return Err(anyhow::anyhow!("The config is not an object."))
+        };
+
+        if !fields.contains_key(name) {
+            return Err(anyhow::anyhow!("There is no '{}' in the config.", name));
+        }
+
+        fields.insert(name.to_owned(), serde_json::from_str(value)?);
+
+        let config : GameConfig = serde_json::from_value(serde_json::Value::Object(fields))?;
+        config.validate()?;
+        return Ok(config);
+    }
+}
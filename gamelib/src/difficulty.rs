@@ -0,0 +1,44 @@
+use crate::bonus::*;
+
+/// The difficulty reached after clearing `cleared_rows` rows.
+pub struct DifficultyPoint {
+    pub cleared_rows : u32,
+    pub ball_speed_scale : f32,
+    pub bat_width_scale : f32
+}
+
+/// The difficulty curve of the endless game. The difficulty is interpolated linearly between
+/// the points and stays at the last one afterwards.
+pub const DIFFICULTY_CURVE : [DifficultyPoint; 5] = [
+    DifficultyPoint { cleared_rows: 0, ball_speed_scale: 1f32, bat_width_scale: 1f32 },
+    DifficultyPoint { cleared_rows: 5, ball_speed_scale: 1.15f32, bat_width_scale: 1f32 },
+    DifficultyPoint { cleared_rows: 15, ball_speed_scale: 1.4f32, bat_width_scale: 0.9f32 },
+    DifficultyPoint { cleared_rows: 30, ball_speed_scale: 1.7f32, bat_width_scale: 0.8f32 },
+    DifficultyPoint { cleared_rows: 50, ball_speed_scale: 2f32, bat_width_scale: 0.75f32 }
+];
+
+fn lerp(a : f32, b : f32, t : f32) -> f32 {
+    a + (b - a) * t
+}
+
+pub fn difficulty_modifiers(cleared_rows : u32) -> Modifiers {
+    let last = &DIFFICULTY_CURVE[DIFFICULTY_CURVE.len() - 1];
+    let mut modifiers = Modifiers {
+        ball_speed_scale: last.ball_speed_scale,
+        bat_width_scale: last.bat_width_scale,
+        ..Modifiers::IDENTITY
+    };
+
+    for points in DIFFICULTY_CURVE.windows(2) {
+        let (begin, end) = (&points[0], &points[1]);
+
+        if cleared_rows < end.cleared_rows {
+            let t = (cleared_rows - begin.cleared_rows) as f32 / (end.cleared_rows - begin.cleared_rows) as f32;
+            modifiers.ball_speed_scale = lerp(begin.ball_speed_scale, end.ball_speed_scale, t);
+            modifiers.bat_width_scale = lerp(begin.bat_width_scale, end.bat_width_scale, t);
+            break;
+        }
+    }
+
+    return modifiers;
+}
@@ -0,0 +1,122 @@
+use crate::config;
+use crate::bricks::*;
+use crate::bonus::*;
+use crate::random::*;
+
+/// Decides which cells of a generated row are left empty.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RowPattern {
+    Full,
+    Checker,
+    /// Randomly scattered holes.
+    Gaps,
+    /// Pairs of columns separated by pairs of empty columns.
+    Pillars,
+    /// A band of bricks in the middle of the row.
+    Centered
+}
+
+pub struct PatternDefinition {
+    pub pattern : RowPattern,
+    /// The relative frequency of the pattern.
+    pub weight : u32,
+    /// The pattern is not used before this many rows were generated.
+    pub min_row : u64
+}
+
+pub const ROW_PATTERNS : [PatternDefinition; 5] = [
+    PatternDefinition { pattern: RowPattern::Full, weight: 50, min_row: 0 },
+    PatternDefinition { pattern: RowPattern::Checker, weight: 10, min_row: 0 },
+    PatternDefinition { pattern: RowPattern::Gaps, weight: 20, min_row: 0 },
+    PatternDefinition { pattern: RowPattern::Pillars, weight: 10, min_row: 10 },
+    PatternDefinition { pattern: RowPattern::Centered, weight: 10, min_row: 10 }
+];
+
+/// The content of a single cell of a generated row.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cell {
+    pub kind : BrickKind,
+    pub bonus : Option<BonusKind>
+}
+
+/// Generates the rows of bricks. A row depends only on the seed and its number, so the initial
+/// grid and every row that slides in later are the same in every game started with the seed.
+pub struct LayoutGenerator {
+    pub seed : u64,
+    pub num_cols : u32
+}
+
+/// Grows from zero for the first row to one for the `config::LAYOUT_RAMP_ROWS`-th one.
+pub fn layout_difficulty(row : u64) -> f32 {
+    let difficulty = row as f32 / config::LAYOUT_RAMP_ROWS as f32;
+    return if difficulty < 1f32 { difficulty } else { 1f32 };
+}
+
+fn random_pattern(random : &mut Random, row : u64) -> RowPattern {
+    let available = ROW_PATTERNS.iter().filter(|pattern| pattern.min_row <= row);
+    let total : u32 = available.clone().map(|pattern| pattern.weight).sum();
+    let mut value = random.next_u32() % total;
+
+    for pattern in available {
+        if value < pattern.weight {
+            return pattern.pattern;
+        }
+
+        value -= pattern.weight;
+    }
+
+    return RowPattern::Full;
+}
+
+fn pattern_mask(pattern : RowPattern, random : &mut Random, row : u64, num_cols : u32) -> Vec<bool> {
+    match pattern {
+        RowPattern::Full => (0..num_cols).map(|_| true).collect(),
+        RowPattern::Checker => (0..num_cols).map(|x| (x as u64 + row).is_multiple_of(2)).collect(),
+        RowPattern::Gaps => (0..num_cols).map(|_| random.next_f32() >= config::LAYOUT_GAP_PROBABILITY).collect(),
+        RowPattern::Pillars => {
+            let phase = random.next_u32() % 2;
+            (0..num_cols).map(|x| (x / 2) % 2 == phase).collect()
+        },
+        RowPattern::Centered => {
+            let margin = random.next_u32() % (num_cols / 3 + 1);
+            (0..num_cols).map(|x| margin <= x && x < num_cols - margin).collect()
+        }
+    }
+}
+
+impl LayoutGenerator {
+    pub fn new(seed : u64, num_cols : u32) -> LayoutGenerator {
+        LayoutGenerator {
+            seed: seed,
+            num_cols: num_cols
+        }
+    }
+
+    /// Generates the `row`-th row of the game, `None` marks the empty cells. Every row contains
+    /// at least one brick that can be destroyed.
+    pub fn row(&self, row : u64) -> Vec<Option<Cell>> {
+        let mut random = Random::new(self.seed ^ row.wrapping_mul(0x9e3779b97f4a7c15u64));
+        let difficulty = layout_difficulty(row);
+        let pattern = random_pattern(&mut random, row);
+        let mask = pattern_mask(pattern, &mut random, row, self.num_cols);
+
+        let mut cells : Vec<Option<Cell>> = mask.iter().map(|present| {
+            let kind = random_brick_kind(&mut random, difficulty);
+            let bonus = random_bonus(&mut random);
+
+            if *present { Some(Cell { kind: kind, bonus: bonus }) } else { None }
+        }).collect();
+
+        let destructible = cells.iter().any(|cell| match cell {
+            Some(cell) => cell.kind != BrickKind::Indestructible,
+            None => false
+        });
+
+        if !destructible {
+            let center = (self.num_cols / 2) as usize;
+            cells[center] = Some(Cell { kind: BrickKind::Normal, bonus: None });
+        }
+
+        return cells;
+    }
+}
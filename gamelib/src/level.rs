@@ -0,0 +1,143 @@
+use serde::{Serialize, Deserialize};
+use crate::config;
+use crate::config::GameConfig;
+use crate::bricks::*;
+use crate::bonus::*;
+use crate::layout::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelBrick {
+    pub col : u32,
+    /// Rows are counted from the top of the arena.
+    pub row : u32,
+    pub kind : BrickKind,
+    #[serde(default)]
+    pub bonus : Option<BonusKind>
+}
+
+/// A hand-made level. Unlike the generated games its rows are not refilled, the level ends when
+/// every brick that can be destroyed is destroyed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    pub name : String,
+    pub num_cols : u32,
+    pub num_rows : u32,
+    /// Overrides the `ball_velocity` of the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ball_velocity : Option<f32>,
+    /// Overrides the `bat_width` of the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bat_width : Option<f32>,
+    pub bricks : Vec<LevelBrick>
+}
+
+/// The levels of the classic campaign, in order.
+pub const CLASSIC_LEVELS : [&str; 3] = [
+    include_str!("../levels/classic-1.json"),
+    include_str!("../levels/classic-2.json"),
+    include_str!("../levels/classic-3.json")
+];
+
+impl Level {
+    /// The number of the cells of the level, an error if the grid is empty or too large.
+    pub fn num_cells(&self) -> anyhow::Result<usize> {
+        if self.num_cols == 0 || self.num_rows == 0 {
+            return Err(anyhow::anyhow!("The level '{}' has no cells.", self.name));
+        }
+
+        if self.num_cols > config::MAX_GRID_COLS || self.num_rows > config::MAX_GRID_ROWS {
+            return Err(anyhow::anyhow!(
+                "The level '{}' is larger than {}x{} cells.", self.name, config::MAX_GRID_COLS, config::MAX_GRID_ROWS));
+        }
+
+        return match self.num_cols.checked_mul(self.num_rows) {
+            Some(num_cells) => Ok(num_cells as usize),
+            None => Err(anyhow::anyhow!("The level '{}' has too many cells.", self.name))
+        };
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let num_cells = self.num_cells()?;
+
+        let positive = |value : Option<f32>| value.is_none_or(|value| value.is_finite() && value > 0f32);
+
+        if !positive(self.ball_velocity) || !positive(self.bat_width) {
+            return Err(anyhow::anyhow!("The level '{}' has invalid ball velocity or bat width.", self.name));
+        }
+
+        let mut occupied = vec![false; num_cells];
+
+        for brick in &self.bricks {
+            if brick.col >= self.num_cols || brick.row >= self.num_rows {
+                return Err(anyhow::anyhow!(
+                    "The brick at ({}, {}) is outside of the level '{}'.", brick.col, brick.row, self.name));
+            }
+
+            let index = (brick.row * self.num_cols + brick.col) as usize;
+
+            if occupied[index] {
+                return Err(anyhow::anyhow!(
+                    "There are several bricks at ({}, {}) in the level '{}'.", brick.col, brick.row, self.name));
+            }
+
+            occupied[index] = true;
+        }
+
+        if self.bricks.iter().all(|brick| brick.kind == BrickKind::Indestructible) {
+            return Err(anyhow::anyhow!("The level '{}' cannot be cleared.", self.name));
+        }
+
+        return Ok(());
+    }
+
+    pub fn ball_velocity(&self, config : &GameConfig) -> f32 {
+        return self.ball_velocity.unwrap_or(config.ball_velocity);
+    }
+
+    pub fn bat_width(&self, config : &GameConfig) -> f32 {
+        return self.bat_width.unwrap_or(config.bat_width);
+    }
+
+    /// Checks the level against the rules it is played with, the bat has to fit in the arena
+    /// and the bricks of the grid can't be too small.
+    pub fn validate_config(&self, config : &GameConfig) -> anyhow::Result<()> {
+        if self.bat_width(config) >= config.arena_width {
+            return Err(anyhow::anyhow!("The bat of the level '{}' doesn't fit in the arena.", self.name));
+        }
+
+        return config.validate_grid(self.num_cols, self.num_rows);
+    }
+
+    /// The content of every cell of the level, row by row from the top. Fails if the level is
+    /// invalid.
+    pub fn cells(&self) -> anyhow::Result<Vec<Option<Cell>>> {
+        self.validate()?;
+
+        let mut cells = vec![None; self.num_cells()?];
+
+        for brick in &self.bricks {
+            let index = (brick.row * self.num_cols + brick.col) as usize;
+            cells[index] = Some(Cell { kind: brick.kind, bonus: brick.bonus });
+        }
+
+        return Ok(cells);
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        return Ok(serde_json::to_string(self)?);
+    }
+
+    pub fn from_json(json : &str) -> anyhow::Result<Level> {
+        let level : Level = serde_json::from_str(json)?;
+        level.validate()?;
+        return Ok(level);
+    }
+}
+
+pub fn classic_levels() -> anyhow::Result<Vec<Level>> {
+    CLASSIC_LEVELS.iter().map(|json| Level::from_json(json)).collect()
+}
+
+pub fn classic_level(name : &str) -> anyhow::Result<Option<Level>> {
+    return Ok(classic_levels()?.into_iter().find(|level| level.name == name));
+}
@@ -0,0 +1,17 @@
+extern crate nalgebra_glm as glm;
+
+pub mod config;
+pub mod random;
+pub mod collision;
+pub mod bat;
+pub mod ball;
+pub mod bricks;
+pub mod layout;
+pub mod level;
+pub mod bonus;
+pub mod difficulty;
+pub mod audio;
+pub mod simulation;
+pub mod replay;
+
+pub use simulation::*;
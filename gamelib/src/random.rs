@@ -0,0 +1,41 @@
+/// A small PCG32 generator owned by the game. Unlike `js_sys::Math::random` or `rand::StdRng`
+/// its output is fully defined by the seed, so the same seed yields the same game on every
+/// platform and every version of the dependencies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Random {
+    state : u64,
+    increment : u64
+}
+
+const MULTIPLIER : u64 = 6364136223846793005u64;
+const INCREMENT : u64 = 1442695040888963407u64;
+
+impl Random {
+    pub fn new(seed : u64) -> Random {
+        let mut random = Random {
+            state: 0,
+            increment: INCREMENT
+        };
+
+        random.next_u32();
+        random.state = random.state.wrapping_add(seed);
+        random.next_u32();
+
+        return random;
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let state = self.state;
+        self.state = state.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+
+        let xorshifted = (((state >> 18) ^ state) >> 27) as u32;
+        let rotation = (state >> 59) as u32;
+
+        return xorshifted.rotate_right(rotation);
+    }
+
+    /// Returns a value from the `[0, 1)` range.
+    pub fn next_f32(&mut self) -> f32 {
+        return (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32;
+    }
+}
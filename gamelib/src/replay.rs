@@ -0,0 +1,115 @@
+use serde::{Serialize, Deserialize};
+use crate::simulation::*;
+use crate::level::*;
+use crate::config::GameConfig;
+
+/// A number of consecutive simulation steps that share the same input.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputRun {
+    pub steps : u32,
+    pub direction : i8
+}
+
+/// The seed and the run-length encoded per-step input of a game. Together with the game rules
+/// it fully determines the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed : u64,
+    #[serde(default)]
+    pub mode : GameMode,
+    #[serde(default)]
+    pub level : Option<Level>,
+    #[serde(default)]
+    pub config : GameConfig,
+    pub inputs : Vec<InputRun>
+}
+
+impl Replay {
+    pub fn new(seed : u64, mode : GameMode) -> Replay {
+        Replay::with_level(seed, mode, None)
+    }
+
+    pub fn with_level(seed : u64, mode : GameMode, level : Option<Level>) -> Replay {
+        Replay::with_config(seed, mode, level, GameConfig::default())
+    }
+
+    pub fn with_config(seed : u64, mode : GameMode, level : Option<Level>, config : GameConfig) -> Replay {
+        Replay {
+            seed: seed,
+            mode: mode,
+            level: level,
+            config: config,
+            inputs: Vec::new()
+        }
+    }
+
+    /// Appends the input of a single simulation step.
+    pub fn record(&mut self, input : &Input) {
+        let direction = quantize_direction(input.direction);
+
+        if let Some(last) = self.inputs.last_mut() {
+            if last.direction == direction {
+                last.steps += 1;
+                return;
+            }
+        }
+
+        self.inputs.push(InputRun { steps: 1, direction: direction });
+    }
+
+    pub fn num_steps(&self) -> u64 {
+        self.inputs.iter().map(|run| run.steps as u64).sum()
+    }
+
+    pub fn player(&self) -> ReplayPlayer {
+        ReplayPlayer {
+            inputs: self.inputs.clone(),
+            run_index: 0,
+            step_index: 0
+        }
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        return Ok(serde_json::to_string(self)?);
+    }
+
+    pub fn from_json(json : &str) -> anyhow::Result<Replay> {
+        return Ok(serde_json::from_str(json)?);
+    }
+}
+
+/// Yields the recorded inputs one simulation step at a time.
+pub struct ReplayPlayer {
+    inputs : Vec<InputRun>,
+    run_index : usize,
+    step_index : u32
+}
+
+impl Iterator for ReplayPlayer {
+    type Item = Input;
+
+    fn next(&mut self) -> Option<Input> {
+        while let Some(run) = self.inputs.get(self.run_index) {
+            if self.step_index < run.steps {
+                self.step_index += 1;
+                return Some(Input { direction: dequantize_direction(run.direction) });
+            }
+
+            self.run_index += 1;
+            self.step_index = 0;
+        }
+
+        return None;
+    }
+}
+
+/// Re-runs the whole recorded game from scratch.
+pub fn simulate(replay : &Replay) -> anyhow::Result<Simulation> {
+    let mut simulation = Simulation::with_config(replay.seed, replay.mode, replay.level.clone(), replay.config.clone())?;
+
+    for input in replay.player() {
+        step(&mut simulation, &input)?;
+    }
+
+    return Ok(simulation);
+}
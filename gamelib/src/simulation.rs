@@ -0,0 +1,272 @@
+use glm::*;
+use crate::config;
+use crate::config::GameConfig;
+use crate::bat::*;
+use crate::ball::*;
+use crate::bricks::*;
+use crate::bonus::*;
+use crate::difficulty::*;
+use crate::random::*;
+use crate::level::*;
+use crate::audio::*;
+use serde::{Serialize, Deserialize};
+use std::cmp::{max};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameStage {
+    Gameplay,
+    /// The game is frozen, the steps do nothing until it's resumed.
+    Paused,
+    GameOver,
+    ScoreBoard
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    /// Every life starts with several balls bouncing off each other.
+    Chaos
+}
+
+/// The player input applied during a single simulation step.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Input {
+    /// Horizontal direction of the bat movement in the `[-1, 1]` range.
+    pub direction : f32
+}
+
+impl Input {
+    /// Rounds the input to the precision stored in replays, so a replayed game sees exactly the
+    /// same values as the recorded one.
+    pub fn quantize(&self) -> Input {
+        Input { direction: dequantize_direction(quantize_direction(self.direction)) }
+    }
+}
+
+pub fn quantize_direction(direction : f32) -> i8 {
+    let direction = direction.clamp(-1f32, 1f32);
+    return (direction * 127f32).round() as i8;
+}
+
+pub fn dequantize_direction(direction : i8) -> f32 {
+    return direction as f32 / 127f32;
+}
+
+pub struct Simulation {
+    pub stage : GameStage,
+    pub bat : Bat,
+    pub balls : Vec<Ball>,
+    pub bricks : Bricks,
+    pub bonuses : Vec<Bonus>,
+    pub effects : Effects,
+    pub time : f64,
+    pub score : i64,
+    pub lives : u32,
+    pub game_over_time : f64,
+    pub mode : GameMode,
+    pub seed : u64,
+    pub random : Random,
+    /// The hand-made level, `None` for the endless generated games.
+    pub level : Option<Level>,
+    pub config : GameConfig,
+    pub ball_velocity : f32,
+    pub bat_width : f32,
+    /// The sounds triggered during the last step.
+    pub sounds : Vec<SoundEffect>,
+    /// The bounces of the balls during the last step.
+    pub impacts : Vec<Impact>
+}
+
+impl Simulation {
+    /// Creates a new classic game. Every random decision made during the game is derived from
+    /// the `seed`.
+    pub fn new(seed : u64) -> Simulation {
+        Simulation::with_mode(seed, GameMode::Classic)
+    }
+
+    pub fn with_mode(seed : u64, mode : GameMode) -> Simulation {
+        Simulation::with_level(seed, mode, None).expect("The default generated game is valid.")
+    }
+
+    /// Fails if the level is invalid.
+    pub fn with_level(seed : u64, mode : GameMode, level : Option<Level>) -> anyhow::Result<Simulation> {
+        Simulation::with_config(seed, mode, level, GameConfig::default())
+    }
+
+    /// Creates a game with the rules from the `config`, the ball velocity and the bat width of
+    /// the level take precedence over the ones from the config. Fails if the level is invalid.
+    pub fn with_config(seed : u64, mode : GameMode, level : Option<Level>, config : GameConfig) -> anyhow::Result<Simulation> {
+        config.validate()?;
+
+        if let Some(level) = &level {
+            level.validate_config(&config)?;
+        }
+
+        let random = Random::new(seed);
+
+        let bricks = match &level {
+            Some(level) => Bricks::from_level(&config, level)?,
+            None => Bricks::new(&config, seed)
+        };
+
+        let ball_velocity = level.as_ref().map_or(config.ball_velocity, |level| level.ball_velocity(&config));
+        let bat_width = level.as_ref().map_or(config.bat_width, |level| level.bat_width(&config));
+
+        let mut simulation = Simulation {
+            stage: GameStage::Gameplay,
+            bat: Bat::new(&config),
+            balls: Vec::new(),
+            bricks: bricks,
+            bonuses: Vec::new(),
+            effects: Effects::new(),
+            time: 0f64,
+            score: 0,
+            lives: config.num_lives,
+            game_over_time: 0f64,
+            mode: mode,
+            seed: seed,
+            random: random,
+            level: level,
+            config: config,
+            ball_velocity: ball_velocity,
+            bat_width: bat_width,
+            sounds: Vec::new(),
+            impacts: Vec::new()
+        };
+
+        simulation.bat.size.x = bat_width;
+
+        reset_balls(&mut simulation);
+
+        return Ok(simulation);
+    }
+
+    pub fn pause(&mut self) {
+        if self.stage == GameStage::Gameplay {
+            self.stage = GameStage::Paused;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.stage == GameStage::Paused {
+            self.stage = GameStage::Gameplay;
+        }
+    }
+
+    /// The modifiers that ramp up the game as the rows are cleared.
+    pub fn difficulty(&self) -> Modifiers {
+        difficulty_modifiers(self.bricks.num_refilled_rows())
+    }
+}
+
+fn reset_balls(simulation : &mut Simulation) {
+    let num_balls = match simulation.mode {
+        GameMode::Classic => 1,
+        GameMode::Chaos => config::CHAOS_NUM_BALLS
+    };
+
+    simulation.balls.clear();
+
+    for i in 0..num_balls {
+        let mut ball = Ball::new(&simulation.config);
+        ball.reset_position(&simulation.config, simulation.random.next_f32(), simulation.ball_velocity);
+        ball.position.x += (i as f32 - (num_balls - 1) as f32 * 0.5f32) * ball.size * 3f32;
+        simulation.balls.push(ball);
+    }
+}
+
+fn spawn_ball(simulation : &mut Simulation) {
+    let mut ball = Ball::new(&simulation.config);
+    ball.reset_position(&simulation.config, simulation.random.next_f32(), simulation.ball_velocity);
+    ball.position = simulation.bat.position - vec2(0f32, simulation.bat.size.y * 0.5f32 + ball.size + 1f32);
+    ball.freeze_time = None;
+    simulation.balls.push(ball);
+}
+
+fn decrease_lives(simulation : &mut Simulation) {
+    simulation.lives = max(simulation.lives, 1) - 1;
+    simulation.bonuses.clear();
+    simulation.effects.clear();
+
+    if simulation.lives != 0 {
+        simulation.sounds.push(SoundEffect::LifeLost);
+        reset_balls(simulation);
+    }
+    else {
+        simulation.sounds.push(SoundEffect::GameOver);
+        simulation.stage = GameStage::GameOver;
+        simulation.game_over_time = simulation.time;
+    }
+}
+
+/// Advances the simulation by a single `config::TIME_STEP`.
+pub fn step(simulation : &mut Simulation, input : &Input) -> anyhow::Result<()> {
+    simulation.sounds.clear();
+    simulation.impacts.clear();
+    simulation.bricks.destroyed.clear();
+
+    if simulation.stage == GameStage::Paused {
+        return Ok(());
+    }
+
+    let elapsed = config::TIME_STEP as f32;
+    let input = input.quantize();
+    simulation.time += config::TIME_STEP;
+
+    match simulation.stage {
+        GameStage::Gameplay => {
+            let modifiers = simulation.effects.modifiers().combine(&simulation.difficulty());
+
+            simulation.bat.input = vec2(input.direction, 0f32);
+            simulation.bat.size.x = simulation.bat_width * modifiers.bat_width_scale;
+
+            // The bat moves first, the balls are swept against its movement.
+            update_bat(&simulation.config, &mut simulation.bat, elapsed)?;
+
+            let old_positions : Vec<Vec2> = simulation.balls.iter().map(|ball| ball.position).collect();
+            let mut out_of_arena : Vec<bool> = Vec::new();
+
+            for ball in &mut simulation.balls {
+                let ball_status = update_ball(&simulation.config, &simulation.bat, ball, &mut simulation.bricks, &modifiers, elapsed)?;
+
+                simulation.score += ball_status.points * modifiers.score_multiplier;
+                simulation.bonuses.extend(ball_status.bonuses);
+                simulation.sounds.extend(ball_status.sounds);
+                simulation.impacts.extend(ball_status.impacts);
+                out_of_arena.push(ball_status.out_of_arena);
+            }
+
+            resolve_ball_collisions(&mut simulation.balls, &old_positions, &modifiers, elapsed);
+
+            let mut index = 0;
+            simulation.balls.retain(|_| { index += 1; !out_of_arena[index - 1] });
+
+            for kind in update_bonuses(&simulation.config, &mut simulation.bonuses, &simulation.bat, elapsed) {
+                for _ in 0..effect_definition(kind).extra_balls {
+                    spawn_ball(simulation);
+                }
+
+                simulation.effects.add(kind);
+            }
+
+            simulation.effects.update(elapsed);
+
+            if simulation.balls.is_empty() {
+                decrease_lives(simulation);
+            }
+            else if simulation.level.is_some() && simulation.bricks.cleared() {
+                simulation.stage = GameStage::GameOver;
+                simulation.game_over_time = simulation.time;
+            }
+        },
+        GameStage::GameOver if simulation.time - simulation.game_over_time > config::GAME_OVER_PAUSE_TIME => {
+            simulation.stage = GameStage::ScoreBoard
+        }
+        _ => {}
+    };
+
+    simulation.bricks.update(elapsed)?;
+
+    return Ok(());
+}
@@ -0,0 +1,62 @@
+extern crate nalgebra_glm as glm;
+
+use gamelib::*;
+use gamelib::audio::*;
+use glm::vec2;
+
+const SAMPLE_RATE : u32 = 44100;
+
+const ALL_SOUNDS : [SoundEffect; 5] = [
+    SoundEffect::BrickHit,
+    SoundEffect::BatHit,
+    SoundEffect::WallBounce,
+    SoundEffect::LifeLost,
+    SoundEffect::GameOver
+];
+
+#[test]
+fn test_synthesize_is_deterministic() {
+    for effect in &ALL_SOUNDS {
+        assert_eq!(synthesize(*effect, SAMPLE_RATE), synthesize(*effect, SAMPLE_RATE));
+    }
+}
+
+#[test]
+fn test_synthesized_samples() {
+    for effect in &ALL_SOUNDS {
+        let sound = sound_definition(*effect);
+        let samples = synthesize(*effect, SAMPLE_RATE);
+
+        assert_eq!((sound.duration * SAMPLE_RATE as f32) as usize, samples.len());
+        assert!(samples.iter().all(|sample| sample.abs() <= 1f32));
+        assert!(samples.iter().any(|sample| sample.abs() > 0.1f32), "{:?} is silent", effect);
+
+        // The sounds start and end quietly, so they don't click.
+        assert_eq!(0f32, samples[0]);
+        assert!(samples[samples.len() - 1].abs() < 0.01f32);
+    }
+}
+
+#[test]
+fn test_simulation_sounds() {
+    let mut simulation = Simulation::new(42);
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(config::BALL_START_SIZE + 5f32, 500f32);
+    simulation.balls[0].velocity = vec2(-config::BALL_VELOCITY, 0f32);
+
+    step(&mut simulation, &Input::default()).unwrap();
+    assert_eq!(vec![SoundEffect::WallBounce], simulation.sounds);
+
+    step(&mut simulation, &Input::default()).unwrap();
+    assert!(simulation.sounds.is_empty());
+
+    simulation.lives = 1;
+    simulation.balls[0].position = vec2(100f32, config::GAME_AREA_HEIGHT as f32 - 10f32);
+    simulation.balls[0].velocity = vec2(0f32, config::BALL_VELOCITY);
+
+    while simulation.stage == GameStage::Gameplay {
+        step(&mut simulation, &Input::default()).unwrap();
+    }
+
+    assert_eq!(vec![SoundEffect::GameOver], simulation.sounds);
+}
@@ -0,0 +1,134 @@
+extern crate nalgebra_glm as glm;
+
+use gamelib::*;
+use gamelib::ball::*;
+use gamelib::bat::*;
+use glm::vec2;
+
+fn angle(velocity : glm::Vec2) -> f32 {
+    velocity.x.atan2(-velocity.y)
+}
+
+fn bounce(bat : &Bat, bat_velocity : f32, offset : f32) -> glm::Vec2 {
+    let contact = vec2(bat.position.x + offset, bat.position.y - bat.size.y * 0.5f32);
+    return bat_rebound_velocity(&config::GameConfig::default(), bat, vec2(bat_velocity, 0f32), vec2(0f32, config::BALL_VELOCITY), contact);
+}
+
+#[test]
+fn test_rebound_angle_follows_hit_position() {
+    let bat = Bat::new(&config::GameConfig::default());
+    let half_width = bat.size.x * 0.5f32;
+
+    let center = bounce(&bat, 0f32, 0f32);
+    assert!(center.x.abs() < 0.001f32);
+    assert!(center.y < 0f32);
+
+    let right = bounce(&bat, 0f32, half_width * 0.5f32);
+    let left = bounce(&bat, 0f32, -half_width * 0.5f32);
+    assert!(right.x > 0f32 && right.y < 0f32);
+    assert!(left.x < 0f32 && left.y < 0f32);
+    assert!((angle(right) + angle(left)).abs() < 0.001f32);
+
+    let edge = bounce(&bat, 0f32, half_width * 2f32);
+    assert!((angle(edge) - config::BAT_MAX_REBOUND_ANGLE).abs() < 0.001f32);
+    assert!((glm::length(&edge) - config::BALL_VELOCITY).abs() < 0.1f32);
+}
+
+#[test]
+fn test_moving_bat_adds_english() {
+    let bat = Bat::new(&config::GameConfig::default());
+
+    let center = bounce(&bat, config::BAT_VELOCITY, 0f32);
+    assert!((angle(center) - config::BAT_ENGLISH_ANGLE).abs() < 0.001f32);
+
+    let edge = bounce(&bat, config::BAT_VELOCITY, bat.size.x * 0.5f32);
+    assert!((angle(edge) - config::BAT_MAX_REBOUND_ANGLE).abs() < 0.001f32);
+
+    let center = bounce(&bat, -config::BAT_VELOCITY, 0f32);
+    assert!((angle(center) + config::BAT_ENGLISH_ANGLE).abs() < 0.001f32);
+
+    // The english follows the speed of the bat, not the input.
+    let center = bounce(&bat, config::BAT_VELOCITY * 0.5f32, 0f32);
+    assert!((angle(center) - config::BAT_ENGLISH_ANGLE * 0.5f32).abs() < 0.001f32);
+}
+
+#[test]
+fn test_bat_at_wall_adds_no_english() {
+    let mut simulation = Simulation::new(42);
+
+    for _ in 0..100 {
+        step(&mut simulation, &Input { direction: -1f32 }).unwrap();
+    }
+
+    let bat = &simulation.bat;
+    let ball_size = simulation.balls[0].size;
+
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(bat.position.x, bat.position.y - bat.size.y * 0.5f32 - ball_size - 5f32);
+    simulation.balls[0].velocity = vec2(0f32, config::BALL_VELOCITY);
+
+    step(&mut simulation, &Input { direction: -1f32 }).unwrap();
+
+    assert!(simulation.balls[0].velocity.y < 0f32);
+    assert!(simulation.balls[0].velocity.x.abs() < 0.1f32);
+}
+
+#[test]
+fn test_ball_steered_by_bat() {
+    let mut simulation = Simulation::new(42);
+    let bat = &simulation.bat;
+
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(bat.position.x + bat.size.x * 0.25f32, bat.position.y - 60f32);
+    simulation.balls[0].velocity = vec2(0f32, config::BALL_VELOCITY);
+
+    for _ in 0..10 {
+        step(&mut simulation, &Input::default()).unwrap();
+    }
+
+    assert!(simulation.balls[0].velocity.x > 0f32);
+    assert!(simulation.balls[0].velocity.y < 0f32);
+}
+
+fn overlaps_bat(simulation : &Simulation) -> bool {
+    let ball = &simulation.balls[0];
+    let bat = &simulation.bat;
+
+    return collision::circle_aabb_penetration(ball.position, ball.size, bat.position, bat.size * 0.5f32).is_some();
+}
+
+#[test]
+fn test_moving_bat_pushes_ball() {
+    let mut simulation = Simulation::new(42);
+    let bat_position = simulation.bat.position;
+    let bat_size = simulation.bat.size;
+    let ball_size = simulation.balls[0].size;
+
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(bat_position.x + bat_size.x * 0.5f32 + ball_size + 2f32, bat_position.y);
+    simulation.balls[0].velocity = vec2(0f32, config::BALL_VELOCITY);
+
+    for _ in 0..3 {
+        step(&mut simulation, &Input { direction: 1f32 }).unwrap();
+        assert!(!overlaps_bat(&simulation));
+    }
+
+    assert!(simulation.balls[0].velocity.x > 0f32);
+    assert!(simulation.balls[0].position.x > simulation.bat.position.x + simulation.bat.size.x * 0.5f32);
+}
+
+#[test]
+fn test_ball_inside_bat_is_pushed_out() {
+    let mut simulation = Simulation::new(42);
+    let bat_position = simulation.bat.position;
+
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = bat_position - vec2(0f32, 5f32);
+    simulation.balls[0].velocity = vec2(0f32, config::BALL_VELOCITY);
+
+    step(&mut simulation, &Input::default()).unwrap();
+
+    assert!(!overlaps_bat(&simulation));
+    assert!(simulation.balls[0].position.y < bat_position.y);
+    assert!(simulation.balls[0].velocity.y < 0f32);
+}
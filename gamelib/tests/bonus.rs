@@ -0,0 +1,117 @@
+extern crate nalgebra_glm as glm;
+
+use gamelib::*;
+use gamelib::bonus::*;
+use glm::vec2;
+
+const SEED : u64 = 42;
+
+#[test]
+fn test_effects_combine_and_expire() {
+    let mut effects = Effects::new();
+
+    effects.add(BonusKind::WidenBat);
+    effects.add(BonusKind::DoublePoints);
+    effects.add(BonusKind::Blockage);
+
+    let modifiers = effects.modifiers();
+    assert_eq!(1.5f32, modifiers.bat_width_scale);
+    assert_eq!(2, modifiers.score_multiplier);
+    assert!(modifiers.floor);
+
+    effects.update(effect_definition(BonusKind::Blockage).duration + 0.1f32);
+    assert!(!effects.modifiers().floor);
+    assert_eq!(2, effects.active.len());
+
+    effects.add(BonusKind::WidenBat);
+    assert_eq!(2, effects.active.len());
+
+    effects.update(60f32);
+    assert_eq!(Modifiers::IDENTITY, effects.modifiers());
+}
+
+#[test]
+fn test_bonus_released_from_brick() {
+    let mut simulation = Simulation::new(SEED);
+
+    for brick in &mut simulation.bricks.bricks {
+        brick.bonus = Some(BonusKind::SlowDown);
+    }
+
+    simulation.balls[0].freeze_time = None;
+
+    while simulation.score == 0 {
+        step(&mut simulation, &Input::default()).unwrap();
+    }
+
+    assert!(!simulation.bonuses.is_empty());
+    assert!(simulation.bonuses.iter().all(|bonus| bonus.kind == BonusKind::SlowDown));
+}
+
+#[test]
+fn test_bat_catches_bonus() {
+    let mut simulation = Simulation::new(SEED);
+    let position = simulation.bat.position - vec2(0f32, 100f32);
+
+    simulation.bonuses.push(Bonus::new(BonusKind::WidenBat, position));
+
+    for _ in 0..100 {
+        step(&mut simulation, &Input::default()).unwrap();
+    }
+
+    assert!(simulation.bonuses.is_empty());
+    assert_eq!(vec![BonusKind::WidenBat], simulation.effects.active.iter().map(|effect| effect.kind).collect::<Vec<_>>());
+    assert_eq!(config::BAT_WIDTH * 1.5f32, simulation.bat.size.x);
+}
+
+#[test]
+fn test_missed_bonus_falls_out() {
+    let mut simulation = Simulation::new(SEED);
+    let position = vec2(30f32, simulation.bat.position.y - 50f32);
+
+    for brick in &mut simulation.bricks.bricks {
+        brick.bonus = None;
+    }
+
+    simulation.bat.position.x = config::GAME_AREA_WIDTH as f32 - simulation.bat.size.x;
+    simulation.bonuses.push(Bonus::new(BonusKind::WidenBat, position));
+
+    for _ in 0..200 {
+        step(&mut simulation, &Input::default()).unwrap();
+    }
+
+    assert!(simulation.bonuses.is_empty());
+    assert!(simulation.effects.active.is_empty());
+}
+
+#[test]
+fn test_blockage_keeps_ball_in_arena() {
+    let mut simulation = Simulation::new(SEED);
+    simulation.effects.add(BonusKind::Blockage);
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(100f32, config::GAME_AREA_HEIGHT as f32 - 30f32);
+    simulation.balls[0].velocity = vec2(0f32, config::BALL_VELOCITY);
+
+    for _ in 0..10 {
+        step(&mut simulation, &Input::default()).unwrap();
+    }
+
+    assert_eq!(config::NUM_LIVES, simulation.lives);
+    assert!(simulation.balls[0].velocity.y < 0f32);
+}
+
+#[test]
+fn test_extra_ball_bonus() {
+    let mut simulation = Simulation::new(SEED);
+    let position = simulation.bat.position - vec2(0f32, 100f32);
+
+    simulation.bonuses.push(Bonus::new(BonusKind::ExtraBall, position));
+
+    for _ in 0..100 {
+        step(&mut simulation, &Input::default()).unwrap();
+    }
+
+    assert_eq!(2, simulation.balls.len());
+    assert!(simulation.balls[1].freeze_time.is_none());
+    assert!(simulation.effects.active.is_empty());
+}
@@ -0,0 +1,138 @@
+extern crate nalgebra_glm as glm;
+use gamelib::bricks::*;
+use gamelib::random::*;
+use gamelib::config;
+use glm::*;
+
+const SEED : u64 = 42;
+
+fn normal_bricks() -> Bricks {
+    let mut bricks = Bricks::new(&config::GameConfig::default(), SEED);
+
+    for brick in &mut bricks.bricks {
+        brick.set_kind(BrickKind::Normal);
+        brick.bonus = None;
+        brick.destruction_time = None;
+    }
+
+    return bricks;
+}
+
+#[test]
+fn test_strong_brick_needs_several_hits() {
+    let mut bricks = normal_bricks();
+    let mut released = Vec::new();
+    bricks.bricks[0].set_kind(BrickKind::Strong);
+
+    let hit_points = brick_definition(BrickKind::Strong).hit_points;
+
+    for _ in 1..hit_points {
+        assert_eq!(0, bricks.hit(0, &mut released));
+        assert!(bricks.bricks[0].destruction_time.is_none());
+    }
+
+    assert_eq!(brick_definition(BrickKind::Strong).score, bricks.hit(0, &mut released));
+    assert!(bricks.bricks[0].destruction_time.is_some());
+}
+
+#[test]
+fn test_indestructible_brick() {
+    let mut bricks = normal_bricks();
+    let mut released = Vec::new();
+    let last_row = bricks.last_row() as usize * bricks.num_cols as usize;
+
+    bricks.bricks[last_row].set_kind(BrickKind::Indestructible);
+
+    for index in last_row..(last_row + bricks.num_cols as usize) {
+        for _ in 0..10 {
+            bricks.hit(index, &mut released);
+        }
+    }
+
+    assert!(bricks.bricks[last_row].destruction_time.is_none());
+    assert!(bricks.last_row_empty());
+}
+
+#[test]
+fn test_explosive_brick_destroys_neighbours() {
+    let mut bricks = normal_bricks();
+    let mut released = Vec::new();
+    let num_cols = bricks.num_cols as usize;
+    let center = 3 * num_cols + 3;
+
+    bricks.bricks[center].set_kind(BrickKind::Explosive);
+    bricks.bricks[center + 1].set_kind(BrickKind::Explosive);
+    bricks.bricks[center - num_cols].set_kind(BrickKind::Indestructible);
+
+    let points = bricks.hit(center, &mut released);
+
+    let destroyed : Vec<usize> = (0..bricks.bricks.len())
+        .filter(|index| bricks.bricks[*index].destruction_time.is_some())
+        .collect();
+
+    let expected = vec![
+        center - num_cols - 1, center - num_cols + 1, center - num_cols + 2,
+        center - 1, center, center + 1, center + 2,
+        center + num_cols - 1, center + num_cols, center + num_cols + 1, center + num_cols + 2];
+
+    assert_eq!(expected, destroyed);
+    assert_eq!(9 + 2 * brick_definition(BrickKind::Explosive).score, points);
+    assert_eq!(expected.len(), bricks.destroyed.len());
+    assert_eq!(bricks.bricks[center].position, bricks.destroyed[0].position);
+}
+
+#[test]
+fn test_neighbours_follow_row_shift() {
+    let mut bricks = normal_bricks();
+    let num_cols = bricks.num_cols as usize;
+    let last_row = bricks.last_row() as usize;
+
+    bricks.reset_last_row();
+
+    let mut top_neighbours = bricks.neighbours(last_row * num_cols);
+    top_neighbours.sort();
+
+    assert_eq!(vec![0, 1, last_row * num_cols + 1], top_neighbours);
+}
+
+fn overlapping_bricks(bricks : &Bricks, min : Vec2, max : Vec2) -> Vec<usize> {
+    let mut result : Vec<usize> = (0..bricks.bricks.len()).filter(|index| {
+        let brick = &bricks.bricks[*index];
+        let brick_min = brick.position - brick.size * 0.5f32;
+        let brick_max = brick.position + brick.size * 0.5f32;
+        brick_min.x <= max.x && min.x <= brick_max.x && brick_min.y <= max.y && min.y <= brick_max.y
+    }).collect();
+
+    result.sort();
+    return result;
+}
+
+#[test]
+fn test_query_finds_overlapping_bricks() {
+    let mut bricks = normal_bricks();
+    let mut random = Random::new(SEED);
+    let game_area = vec2(config::GAME_AREA_WIDTH as f32, config::GAME_AREA_HEIGHT as f32);
+
+    bricks.reset_last_row();
+
+    for step in 0..200 {
+        bricks.update(0.01f32).unwrap();
+
+        let corner = vec2(random.next_f32() * 1.2f32 - 0.1f32, random.next_f32() * 0.8f32 - 0.1f32);
+        let min = corner.component_mul(&game_area);
+        let max = min + vec2(random.next_f32(), random.next_f32()) * 100f32;
+
+        let mut candidates = bricks.query(min, max);
+        candidates.sort();
+
+        for index in overlapping_bricks(&bricks, min, max) {
+            assert!(candidates.contains(&index), "step {}: brick {} was not found", step, index);
+        }
+    }
+
+    assert_eq!(0f32, bricks.origin.y);
+
+    for index in 0..bricks.bricks.len() {
+        assert_eq!(bricks.cell_position(index), bricks.bricks[index].position);
+    }
+}
@@ -0,0 +1,87 @@
+extern crate nalgebra_glm as glm;
+
+use gamelib::*;
+use gamelib::ball::*;
+use gamelib::bat::*;
+use gamelib::bonus::*;
+use gamelib::bricks::*;
+use gamelib::collision::*;
+use gamelib::level::*;
+use glm::vec2;
+
+#[test]
+fn test_corner_collision() {
+    let collision = resolve_circle_aabb_collision(
+        vec2(20f32, 20f32),
+        vec2(10f32, 10f32),
+        5f32,
+        vec2(0f32, 0f32),
+        vec2(10f32, 10f32)).unwrap();
+
+    let expected_t = (10f32 - 5f32 / 2f32.sqrt()) / 10f32;
+    let expected_normal = glm::normalize(&vec2(1f32, 1f32));
+
+    assert!((expected_t - collision.t).abs() < 0.0001f32);
+    assert!(glm::distance(&expected_normal, &collision.normal) < 0.0001f32);
+    assert!(glm::distance(&vec2(10f32, 10f32), &collision.point) < 0.0001f32);
+}
+
+#[test]
+fn test_corner_miss() {
+    // The path crosses the bounding box of the rounded corner, but not the corner itself.
+    let collision = resolve_circle_aabb_collision(
+        vec2(20f32, 8f32),
+        vec2(8f32, 20f32),
+        5f32,
+        vec2(0f32, 0f32),
+        vec2(10f32, 10f32));
+
+    assert!(collision.is_none());
+}
+
+#[test]
+fn test_moving_away_is_not_a_collision() {
+    let collision = resolve_circle_aabb_collision(
+        vec2(0f32, -15f32),
+        vec2(0f32, -25f32),
+        5f32,
+        vec2(0f32, 0f32),
+        vec2(10f32, 10f32));
+
+    assert!(collision.is_none());
+}
+
+#[test]
+fn test_inside_collision() {
+    let collision = resolve_circle_aabb_inv_collision(
+        vec2(50f32, 50f32),
+        vec2(50f32, 150f32),
+        10f32,
+        vec2(50f32, 50f32),
+        vec2(50f32, 50f32)).unwrap();
+
+    assert_eq!(vec2(0f32, -1f32), collision.normal);
+    assert!((collision.t - 0.4f32).abs() < 0.0001f32);
+    assert_eq!(vec2(50f32, 100f32), collision.point);
+}
+
+#[test]
+fn test_several_collisions_in_one_step() {
+    let level = Level::from_json(r#"{ "name": "single", "num_cols": 10, "num_rows": 1, "bricks": [
+        { "col": 9, "row": 0, "kind": "Normal" } ] }"#).unwrap();
+    let mut bricks = Bricks::from_level(&config::GameConfig::default(), &level).unwrap();
+
+    let mut ball = Ball::new(&config::GameConfig::default());
+    ball.freeze_time = None;
+    ball.position = vec2(ball.size + 10f32, ball.size + 20f32);
+    ball.velocity = vec2(-config::BALL_VELOCITY, -config::BALL_VELOCITY);
+
+    // The ball runs into the left wall and then into the top wall within the step.
+    let modifiers = Modifiers { ball_speed_scale: 3f32, ..Modifiers::IDENTITY };
+
+    update_ball(&config::GameConfig::default(), &Bat::new(&config::GameConfig::default()), &mut ball, &mut bricks, &modifiers, config::TIME_STEP as f32).unwrap();
+
+    assert!(ball.velocity.x > 0f32);
+    assert!(ball.velocity.y > 0f32);
+    assert!(ball.position.x >= ball.size && ball.position.y >= ball.size);
+}
@@ -0,0 +1,88 @@
+use gamelib::*;
+use gamelib::config::GameConfig;
+use gamelib::replay::*;
+
+const SEED : u64 = 7;
+
+#[test]
+fn test_default_config_matches_constants() {
+    let config = GameConfig::default();
+
+    assert_eq!(config::GAME_AREA_WIDTH as f32, config.arena_width);
+    assert_eq!(config::BALL_VELOCITY, config.ball_velocity);
+    assert_eq!(config::NUM_BRICK_COLS, config.num_brick_cols);
+    assert_eq!(config::NUM_LIVES, config.num_lives);
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_json_overrides_only_given_fields() {
+    let config = GameConfig::from_json(r#"{ "num_lives": 5, "ball_velocity": 600 }"#).unwrap();
+
+    assert_eq!(5, config.num_lives);
+    assert_eq!(600f32, config.ball_velocity);
+    assert_eq!(config::BAT_WIDTH, config.bat_width);
+
+    let json = config.to_json().unwrap();
+    assert_eq!(config, GameConfig::from_json(json.as_str()).unwrap());
+}
+
+#[test]
+fn test_with_field() {
+    let config = GameConfig::default().with_field("num_brick_cols", "6").unwrap();
+
+    assert_eq!(6, config.num_brick_cols);
+    assert!(GameConfig::field_names().contains(&"num_brick_cols".to_owned()));
+    assert!(GameConfig::default().with_field("gravity", "1").is_err());
+    assert!(GameConfig::default().with_field("num_lives", "\"many\"").is_err());
+}
+
+#[test]
+fn test_invalid_config_rejected() {
+    assert!(GameConfig::from_json(r#"{ "num_lives": 0 }"#).is_err());
+    assert!(GameConfig::from_json(r#"{ "ball_velocity": -1 }"#).is_err());
+    assert!(GameConfig::from_json(r#"{ "bat_width": 5000 }"#).is_err());
+    assert!(GameConfig::default().with_field("arena_height", "0").is_err());
+    assert!(GameConfig::default().with_field("bat_max_rebound_angle", "2").is_err());
+    assert!(GameConfig::default().with_field("bat_english_angle", "-0.1").is_err());
+}
+
+#[test]
+fn test_brick_grid_bounded() {
+    assert!(GameConfig::from_json(r#"{ "num_brick_cols": 4294967295 }"#).is_err());
+    assert!(GameConfig::from_json(r#"{ "num_brick_cols": 65536, "num_brick_rows": 65536 }"#).is_err());
+    assert!(GameConfig::from_json(r#"{ "brick_spacing": 1e30 }"#).is_err());
+
+    // The bricks would be narrower than a pixel.
+    assert!(GameConfig::from_json(r#"{ "num_brick_cols": 60 }"#).is_err());
+    assert!(GameConfig::from_json(r#"{ "num_brick_cols": 60, "brick_spacing": 0 }"#).is_ok());
+
+    // The replays carry their configs, they are checked before they are simulated.
+    let config = GameConfig { num_brick_rows: 100000, ..GameConfig::default() };
+
+    assert!(Simulation::with_config(SEED, GameMode::Classic, None, config).is_err());
+}
+
+#[test]
+fn test_simulation_with_custom_config() {
+    let config = GameConfig::from_json(r#"{ "num_brick_cols": 6, "num_brick_rows": 4, "num_lives": 1 }"#).unwrap();
+    let mut simulation = Simulation::with_config(SEED, GameMode::Classic, None, config.clone()).unwrap();
+
+    assert_eq!(6, simulation.bricks.num_cols);
+    assert_eq!(4, simulation.bricks.num_rows);
+    assert_eq!(1, simulation.lives);
+
+    let mut replay = Replay::with_config(SEED, GameMode::Classic, None, config);
+
+    while simulation.stage == GameStage::Gameplay {
+        let input = Input { direction: 0f32 };
+        replay.record(&input);
+        step(&mut simulation, &input).unwrap();
+    }
+
+    let json = replay.to_json().unwrap();
+    let simulated = simulate(&Replay::from_json(json.as_str()).unwrap()).unwrap();
+
+    assert_eq!(simulation.score, simulated.score);
+    assert_eq!(0, simulated.lives);
+}
@@ -0,0 +1,72 @@
+extern crate nalgebra_glm as glm;
+
+use gamelib::*;
+use gamelib::ball::*;
+use gamelib::bat::*;
+use gamelib::bonus::*;
+use gamelib::bricks::*;
+use gamelib::difficulty::*;
+use gamelib::level::*;
+use glm::vec2;
+
+const SEED : u64 = 42;
+
+#[test]
+fn test_difficulty_curve() {
+    assert_eq!(Modifiers::IDENTITY, difficulty_modifiers(0));
+
+    let first = &DIFFICULTY_CURVE[0];
+    let second = &DIFFICULTY_CURVE[1];
+    let middle = difficulty_modifiers((first.cleared_rows + second.cleared_rows) / 2);
+    assert!(first.ball_speed_scale < middle.ball_speed_scale && middle.ball_speed_scale < second.ball_speed_scale);
+
+    let last = &DIFFICULTY_CURVE[DIFFICULTY_CURVE.len() - 1];
+    assert_eq!(last.ball_speed_scale, difficulty_modifiers(last.cleared_rows).ball_speed_scale);
+    assert_eq!(last.bat_width_scale, difficulty_modifiers(last.cleared_rows * 10).bat_width_scale);
+
+    for cleared_rows in 0..last.cleared_rows {
+        let current = difficulty_modifiers(cleared_rows);
+        let next = difficulty_modifiers(cleared_rows + 1);
+        assert!(current.ball_speed_scale <= next.ball_speed_scale);
+        assert!(current.bat_width_scale >= next.bat_width_scale);
+    }
+}
+
+#[test]
+fn test_difficulty_ramps_with_refilled_rows() {
+    let mut simulation = Simulation::new(SEED);
+
+    assert_eq!(Modifiers::IDENTITY, simulation.difficulty());
+
+    for _ in 0..DIFFICULTY_CURVE[2].cleared_rows {
+        simulation.bricks.reset_last_row();
+    }
+
+    let difficulty = simulation.difficulty();
+    assert_eq!(DIFFICULTY_CURVE[2].ball_speed_scale, difficulty.ball_speed_scale);
+
+    step(&mut simulation, &Input::default()).unwrap();
+
+    assert_eq!(config::BAT_WIDTH * difficulty.bat_width_scale, simulation.bat.size.x);
+}
+
+#[test]
+fn test_fast_ball_does_not_tunnel() {
+    let level = Level::from_json(r#"{ "name": "single", "num_cols": 1, "num_rows": 1, "bricks": [
+        { "col": 0, "row": 0, "kind": "Normal" } ] }"#).unwrap();
+    let mut bricks = Bricks::from_level(&config::GameConfig::default(), &level).unwrap();
+    let brick_bottom = bricks.bricks[0].position.y + bricks.bricks[0].size.y * 0.5;
+
+    let mut ball = Ball::new(&config::GameConfig::default());
+    ball.freeze_time = None;
+    ball.position = vec2(bricks.bricks[0].position.x, brick_bottom + ball.size + 5f32);
+    ball.velocity = vec2(0f32, -config::BALL_VELOCITY);
+
+    let modifiers = Modifiers { ball_speed_scale: 15f32, ..Modifiers::IDENTITY };
+    let status = update_ball(&config::GameConfig::default(), &Bat::new(&config::GameConfig::default()), &mut ball, &mut bricks, &modifiers, config::TIME_STEP as f32).unwrap();
+
+    assert_eq!(1, status.brick_hit_count);
+    assert!(bricks.bricks[0].destruction_time.is_some());
+    assert!(ball.velocity.y > 0f32);
+    assert!(ball.position.y > brick_bottom);
+}
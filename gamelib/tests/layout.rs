@@ -0,0 +1,82 @@
+use gamelib::config;
+use gamelib::bricks::*;
+use gamelib::layout::*;
+
+const SEED : u64 = 42;
+
+#[test]
+fn test_same_seed_same_rows() {
+    let first = LayoutGenerator::new(SEED, config::NUM_BRICK_COLS);
+    let second = LayoutGenerator::new(SEED, config::NUM_BRICK_COLS);
+    let third = LayoutGenerator::new(SEED + 1, config::NUM_BRICK_COLS);
+
+    let first : Vec<_> = (0..100).map(|row| first.row(row)).collect();
+    let second : Vec<_> = (0..100).map(|row| second.row(row)).collect();
+    let third : Vec<_> = (0..100).map(|row| third.row(row)).collect();
+
+    assert_eq!(first, second);
+    assert_ne!(first, third);
+}
+
+#[test]
+fn test_rows_vary() {
+    let layout = LayoutGenerator::new(SEED, config::NUM_BRICK_COLS);
+    let rows : Vec<_> = (0..100).map(|row| layout.row(row)).collect();
+
+    assert!(rows.iter().any(|row| row.iter().any(|cell| cell.is_none())));
+    assert!(rows.iter().any(|row| row.iter().all(|cell| cell.is_some())));
+    assert!(rows.windows(2).any(|rows| rows[0] != rows[1]));
+}
+
+#[test]
+fn test_every_row_can_be_cleared() {
+    let layout = LayoutGenerator::new(SEED, config::NUM_BRICK_COLS);
+
+    for row in 0..1000 {
+        let cells = layout.row(row);
+
+        assert_eq!(config::NUM_BRICK_COLS as usize, cells.len());
+        assert!(cells.iter().any(|cell| match cell {
+            Some(cell) => cell.kind != BrickKind::Indestructible,
+            None => false
+        }));
+    }
+}
+
+#[test]
+fn test_difficulty_ramp() {
+    assert_eq!(0f32, layout_difficulty(0));
+    assert_eq!(1f32, layout_difficulty(config::LAYOUT_RAMP_ROWS));
+    assert_eq!(1f32, layout_difficulty(config::LAYOUT_RAMP_ROWS * 10));
+
+    let layout = LayoutGenerator::new(SEED, config::NUM_BRICK_COLS);
+    let count_normal = |rows : std::ops::Range<u64>| rows
+        .flat_map(|row| layout.row(row))
+        .filter(|cell| match cell { Some(cell) => cell.kind == BrickKind::Normal, None => false })
+        .count();
+
+    assert!(count_normal(0..10) > count_normal(1000..1010));
+}
+
+#[test]
+fn test_bricks_follow_layout() {
+    let mut bricks = Bricks::new(&config::GameConfig::default(), SEED);
+    let layout = LayoutGenerator::new(SEED, bricks.num_cols);
+    let num_cols = bricks.num_cols as usize;
+
+    let cell = |brick : &Brick| match brick.destruction_time {
+        Some(_) => None,
+        None => Some(Cell { kind: brick.kind, bonus: brick.bonus })
+    };
+
+    let bottom_row = (bricks.num_rows - 1) as usize * num_cols;
+    let actual : Vec<_> = bricks.bricks[bottom_row..(bottom_row + num_cols)].iter().map(cell).collect();
+    assert_eq!(layout.row(0), actual);
+
+    let last_row = bricks.last_row() as usize * num_cols;
+    bricks.reset_last_row();
+
+    let actual : Vec<_> = bricks.bricks[last_row..(last_row + num_cols)].iter().map(cell).collect();
+    assert_eq!(layout.row(bricks.num_rows as u64), actual);
+    assert_eq!(bricks.num_rows as u64 + 1, bricks.num_generated_rows);
+}
@@ -0,0 +1,145 @@
+extern crate nalgebra_glm as glm;
+
+use gamelib::*;
+use gamelib::bricks::*;
+use gamelib::level::*;
+use gamelib::replay::*;
+
+const SEED : u64 = 42;
+
+const SMALL_LEVEL : &str = r#"{
+    "name": "small",
+    "num_cols": 4,
+    "num_rows": 2,
+    "bricks": [
+        { "col": 0, "row": 0, "kind": "Normal" },
+        { "col": 3, "row": 1, "kind": "Strong", "bonus": "ExtraBall" },
+        { "col": 2, "row": 1, "kind": "Indestructible" }
+    ]
+}"#;
+
+#[test]
+fn test_classic_levels() {
+    let levels = classic_levels().unwrap();
+
+    assert_eq!(CLASSIC_LEVELS.len(), levels.len());
+    assert_eq!(Some(levels[0].clone()), classic_level("classic-1").unwrap());
+    assert_eq!(None, classic_level("missing").unwrap());
+}
+
+#[test]
+fn test_level_bricks() {
+    let level = Level::from_json(SMALL_LEVEL).unwrap();
+
+    assert_eq!(None, level.ball_velocity);
+    assert_eq!(config::BAT_WIDTH, level.bat_width(&config::GameConfig::default()));
+
+    let bricks = Bricks::from_level(&config::GameConfig::default(), &level).unwrap();
+
+    assert_eq!(8, bricks.bricks.len());
+    assert_eq!(4, bricks.num_cols);
+    assert!(bricks.layout.is_none());
+
+    let present : Vec<usize> = (0..bricks.bricks.len())
+        .filter(|index| bricks.bricks[*index].destruction_time.is_none())
+        .collect();
+
+    assert_eq!(vec![0, 6, 7], present);
+    assert_eq!(BrickKind::Strong, bricks.bricks[7].kind);
+    assert_eq!(Some(bonus::BonusKind::ExtraBall), bricks.bricks[7].bonus);
+    assert_eq!(BrickKind::Indestructible, bricks.bricks[6].kind);
+}
+
+#[test]
+fn test_invalid_levels() {
+    let outside = SMALL_LEVEL.replace(r#""col": 3"#, r#""col": 4"#);
+    let duplicate = SMALL_LEVEL.replace(r#""col": 3"#, r#""col": 2"#);
+    let empty = SMALL_LEVEL.replace(r#""num_rows": 2"#, r#""num_rows": 0"#);
+    let indestructible = r#"{ "name": "walls", "num_cols": 1, "num_rows": 1, "bricks": [
+        { "col": 0, "row": 0, "kind": "Indestructible" } ] }"#;
+
+    assert!(Level::from_json(&outside).is_err());
+    assert!(Level::from_json(&duplicate).is_err());
+    assert!(Level::from_json(&empty).is_err());
+    assert!(Level::from_json(indestructible).is_err());
+    assert!(Level::from_json("{}").is_err());
+}
+
+#[test]
+fn test_oversized_levels_rejected() {
+    let huge = SMALL_LEVEL.replace(r#""num_cols": 4"#, r#""num_cols": 4294967295"#);
+    let wide = SMALL_LEVEL.replace(r#""num_cols": 4"#, &format!(r#""num_cols": {}"#, config::MAX_GRID_COLS + 1));
+
+    assert!(Level::from_json(&huge).is_err());
+    assert!(Level::from_json(&wide).is_err());
+
+    // The levels inside the replays are not validated when they are parsed.
+    let level : Level = serde_json::from_str(&huge).unwrap();
+
+    assert!(Bricks::from_level(&config::GameConfig::default(), &level).is_err());
+    assert!(Simulation::with_level(SEED, GameMode::Classic, Some(level)).is_err());
+}
+
+#[test]
+fn test_level_keeps_config_overrides() {
+    let level = Level::from_json(SMALL_LEVEL).unwrap();
+    let config = config::GameConfig { ball_velocity: 600f32, bat_width: 300f32, ..config::GameConfig::default() };
+    let simulation = Simulation::with_config(SEED, GameMode::Classic, Some(level.clone()), config.clone()).unwrap();
+
+    assert_eq!(300f32, simulation.bat.size.x);
+    assert_eq!(600f32, simulation.ball_velocity);
+    assert!(!level.to_json().unwrap().contains("ball_velocity"));
+
+    // The bat of the level has to fit in the arena of the config.
+    let wide = SMALL_LEVEL.replace(r#""num_rows": 2,"#, r#""num_rows": 2, "bat_width": 500,"#);
+    let wide = Level::from_json(&wide).unwrap();
+    let narrow = config::GameConfig { arena_width: 400f32, bat_width: 100f32, ..config::GameConfig::default() };
+
+    assert!(Simulation::with_config(SEED, GameMode::Classic, Some(wide), narrow).is_err());
+}
+
+#[test]
+fn test_level_ends_when_cleared() {
+    let level = Level::from_json(SMALL_LEVEL).unwrap();
+    let mut simulation = Simulation::with_level(SEED, GameMode::Classic, Some(level)).unwrap();
+    let mut released = Vec::new();
+
+    step(&mut simulation, &Input::default()).unwrap();
+    assert_eq!(GameStage::Gameplay, simulation.stage);
+
+    simulation.bricks.hit(0, &mut released);
+
+    for _ in 0..3 {
+        simulation.bricks.hit(7, &mut released);
+    }
+
+    for _ in 0..10 {
+        step(&mut simulation, &Input::default()).unwrap();
+    }
+
+    assert_eq!(GameStage::GameOver, simulation.stage);
+    assert!(simulation.bricks.bricks[0].destruction_time.is_some());
+}
+
+#[test]
+fn test_level_replay() {
+    let level = classic_level("classic-2").unwrap().unwrap();
+    let mut simulation = Simulation::with_level(SEED, GameMode::Classic, Some(level.clone())).unwrap();
+    let mut replay = Replay::with_level(SEED, GameMode::Classic, Some(level.clone()));
+
+    assert_eq!(Some(simulation.bat.size.x), level.bat_width);
+    assert_eq!(Some(glm::length(&simulation.balls[0].velocity)), level.ball_velocity);
+
+    for i in 0..1500 {
+        let input = Input { direction: if (i / 60) % 2 == 0 { 1f32 } else { -1f32 } };
+        replay.record(&input);
+        step(&mut simulation, &input).unwrap();
+    }
+
+    let replay = Replay::from_json(&replay.to_json().unwrap()).unwrap();
+    let replayed = simulate(&replay).unwrap();
+
+    assert_eq!(simulation.score, replayed.score);
+    assert_eq!(simulation.lives, replayed.lives);
+    assert_eq!(Some(level), replayed.level);
+}
@@ -0,0 +1,66 @@
+use gamelib::*;
+use gamelib::replay::*;
+
+const SEED : u64 = 7;
+
+fn input_at(index : usize) -> Input {
+    match (index / 50) % 3 {
+        0 => Input { direction: -1f32 },
+        1 => Input { direction: 0.3f32 },
+        _ => Input { direction: 0f32 }
+    }
+}
+
+#[test]
+fn test_record_run_length() {
+    let mut replay = Replay::new(SEED, GameMode::Classic);
+
+    for _ in 0..10 {
+        replay.record(&Input { direction: 1f32 });
+    }
+
+    replay.record(&Input { direction: 0f32 });
+    replay.record(&Input { direction: 0.0001f32 });
+
+    assert_eq!(vec![
+        InputRun { steps: 10, direction: 127 },
+        InputRun { steps: 2, direction: 0 }], replay.inputs);
+    assert_eq!(12, replay.num_steps());
+}
+
+#[test]
+fn test_json_round_trip() {
+    let mut replay = Replay::new(SEED, GameMode::Classic);
+
+    for i in 0..1000 {
+        replay.record(&input_at(i));
+    }
+
+    let json = replay.to_json().unwrap();
+
+    assert_eq!(replay, Replay::from_json(&json).unwrap());
+}
+
+#[test]
+fn test_playback_reproduces_game() {
+    let mut simulation = Simulation::new(SEED);
+    let mut replay = Replay::new(SEED, GameMode::Classic);
+
+    for i in 0..6000 {
+        let input = input_at(i);
+        replay.record(&input);
+        step(&mut simulation, &input).unwrap();
+    }
+
+    let replayed = simulate(&replay).unwrap();
+
+    assert_eq!(simulation.stage, replayed.stage);
+    assert_eq!(simulation.score, replayed.score);
+    assert_eq!(simulation.lives, replayed.lives);
+    assert_eq!(
+        simulation.balls.iter().map(|ball| ball.position).collect::<Vec<_>>(),
+        replayed.balls.iter().map(|ball| ball.position).collect::<Vec<_>>());
+    assert_eq!(simulation.bat.position, replayed.bat.position);
+    assert_eq!(simulation.bricks.origin, replayed.bricks.origin);
+    assert_eq!(simulation.bricks.row_shift, replayed.bricks.row_shift);
+}
@@ -0,0 +1,330 @@
+extern crate nalgebra_glm as glm;
+
+use gamelib::*;
+use gamelib::collision::*;
+use glm::vec2;
+
+const SEED : u64 = 42;
+
+fn run(simulation : &mut Simulation, input : &Input, seconds : f64) {
+    let num_steps = (seconds / config::TIME_STEP) as usize;
+
+    for _ in 0..num_steps {
+        step(simulation, input).unwrap();
+    }
+}
+
+#[test]
+fn test_new_simulation() {
+    let simulation = Simulation::new(SEED);
+
+    assert_eq!(GameStage::Gameplay, simulation.stage);
+    assert_eq!(config::NUM_LIVES, simulation.lives);
+    assert_eq!(0, simulation.score);
+    assert_eq!((config::NUM_BRICK_COLS * config::NUM_BRICK_ROWS) as usize, simulation.bricks.bricks.len());
+    assert!(simulation.balls[0].freeze_time.is_some());
+}
+
+#[test]
+fn test_ball_frozen_after_reset() {
+    let mut simulation = Simulation::new(SEED);
+    let position = simulation.balls[0].position;
+
+    run(&mut simulation, &Input::default(), 0.5);
+
+    assert_eq!(position, simulation.balls[0].position);
+}
+
+#[test]
+fn test_ball_speed_includes_modifiers() {
+    let mut simulation = Simulation::new(SEED);
+
+    step(&mut simulation, &Input::default()).unwrap();
+    assert_eq!(0f32, simulation.balls[0].speed);
+
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(300f32, 500f32);
+    simulation.balls[0].velocity = vec2(0f32, -config::BALL_VELOCITY);
+    simulation.effects.add(bonus::BonusKind::SpeedUp);
+
+    step(&mut simulation, &Input::default()).unwrap();
+
+    let scale = simulation.effects.modifiers().combine(&simulation.difficulty()).ball_speed_scale;
+    assert!(scale > 1f32);
+    assert!((config::BALL_VELOCITY * scale - simulation.balls[0].speed).abs() < 1e-3f32);
+}
+
+#[test]
+fn test_ball_destroys_bricks() {
+    let mut simulation = Simulation::new(SEED);
+    let num_destroyed = |simulation : &Simulation| simulation.bricks.bricks.iter().filter(|brick| brick.destruction_time.is_some()).count();
+    let initially_destroyed = num_destroyed(&simulation);
+
+    run(&mut simulation, &Input::default(), 2.0);
+
+    assert!(simulation.score > 0);
+    assert!(num_destroyed(&simulation) > initially_destroyed);
+}
+
+#[test]
+fn test_bat_stays_in_arena() {
+    let mut simulation = Simulation::new(SEED);
+
+    run(&mut simulation, &Input { direction: -1f32 }, 2.0);
+    assert_eq!(simulation.bat.size.x * 0.5, simulation.bat.position.x);
+
+    run(&mut simulation, &Input { direction: 1f32 }, 2.0);
+    assert_eq!(config::GAME_AREA_WIDTH as f32 - simulation.bat.size.x * 0.5, simulation.bat.position.x);
+}
+
+#[test]
+fn test_life_lost_when_ball_leaves_arena() {
+    let mut simulation = Simulation::new(SEED);
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(100f32, config::GAME_AREA_HEIGHT as f32 - 10f32);
+    simulation.balls[0].velocity = vec2(0f32, config::BALL_VELOCITY);
+
+    run(&mut simulation, &Input::default(), 0.1);
+
+    assert_eq!(config::NUM_LIVES - 1, simulation.lives);
+    assert_eq!(GameStage::Gameplay, simulation.stage);
+    assert!(simulation.balls[0].freeze_time.is_some());
+}
+
+#[test]
+fn test_game_over_and_score_board() {
+    let mut simulation = Simulation::new(SEED);
+    simulation.lives = 1;
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(100f32, config::GAME_AREA_HEIGHT as f32 - 10f32);
+    simulation.balls[0].velocity = vec2(0f32, config::BALL_VELOCITY);
+
+    run(&mut simulation, &Input::default(), 0.1);
+
+    assert_eq!(0, simulation.lives);
+    assert_eq!(GameStage::GameOver, simulation.stage);
+
+    run(&mut simulation, &Input::default(), config::GAME_OVER_PAUSE_TIME + 0.1);
+
+    assert_eq!(GameStage::ScoreBoard, simulation.stage);
+}
+
+#[test]
+fn test_same_seed_same_game() {
+    let mut first = Simulation::new(SEED);
+    let mut second = Simulation::new(SEED);
+
+    for i in 0..2000 {
+        let input = Input { direction: if (i / 70) % 2 == 0 { -1f32 } else { 1f32 } };
+        step(&mut first, &input).unwrap();
+        step(&mut second, &input).unwrap();
+    }
+
+    assert_eq!(
+        first.balls.iter().map(|ball| ball.position).collect::<Vec<_>>(),
+        second.balls.iter().map(|ball| ball.position).collect::<Vec<_>>());
+    assert_eq!(first.bat.position, second.bat.position);
+    assert_eq!(first.score, second.score);
+    assert_eq!(first.lives, second.lives);
+    assert_eq!(first.random, second.random);
+}
+
+#[test]
+fn test_random() {
+    let mut first = random::Random::new(SEED);
+    let mut second = random::Random::new(SEED);
+    let mut third = random::Random::new(SEED + 1);
+
+    let first : Vec<u32> = (0..16).map(|_| first.next_u32()).collect();
+    let second : Vec<u32> = (0..16).map(|_| second.next_u32()).collect();
+    let third : Vec<u32> = (0..16).map(|_| third.next_u32()).collect();
+
+    assert_eq!(first, second);
+    assert_ne!(first, third);
+
+    let mut random = random::Random::new(SEED);
+
+    for _ in 0..1000 {
+        let value = random.next_f32();
+        assert!((0f32..1f32).contains(&value));
+    }
+}
+
+#[test]
+fn test_circle_aabb_collision() {
+    let collision = resolve_circle_aabb_collision(
+        vec2(0f32, -20f32),
+        vec2(0f32, 0f32),
+        5f32,
+        vec2(0f32, 10f32),
+        vec2(10f32, 10f32));
+
+    let collision = collision.unwrap();
+
+    assert_eq!(vec2(0f32, -1f32), collision.normal);
+    assert!((collision.t - 0.75f32).abs() < 0.001f32);
+
+    let collision = resolve_circle_aabb_collision(
+        vec2(-30f32, -20f32),
+        vec2(-30f32, 0f32),
+        5f32,
+        vec2(0f32, 10f32),
+        vec2(10f32, 10f32));
+
+    assert!(collision.is_none());
+}
+
+#[test]
+fn test_circle_inside_aabb() {
+    let collision = resolve_circle_aabb_collision(
+        vec2(-3f32, 8f32),
+        vec2(2f32, 8f32),
+        5f32,
+        vec2(0f32, 10f32),
+        vec2(10f32, 10f32));
+
+    let collision = collision.unwrap();
+    assert_eq!(vec2(-1f32, 0f32), collision.normal);
+    assert_eq!(0f32, collision.t);
+
+    let collision = resolve_circle_aabb_collision(
+        vec2(-3f32, 8f32),
+        vec2(-8f32, 8f32),
+        5f32,
+        vec2(0f32, 10f32),
+        vec2(10f32, 10f32));
+
+    assert!(collision.is_none());
+
+    let penetration = circle_aabb_penetration(vec2(2f32, 2f32), 5f32, vec2(0f32, 10f32), vec2(10f32, 10f32));
+    assert_eq!(Some((vec2(0f32, -1f32), 7f32)), penetration);
+
+    let penetration = circle_aabb_penetration(vec2(13f32, 10f32), 5f32, vec2(0f32, 10f32), vec2(10f32, 10f32));
+    assert_eq!(Some((vec2(1f32, 0f32), 2f32)), penetration);
+
+    assert_eq!(None, circle_aabb_penetration(vec2(16f32, 10f32), 5f32, vec2(0f32, 10f32), vec2(10f32, 10f32)));
+}
+
+#[test]
+fn test_balls_bounce_off_each_other() {
+    let mut simulation = Simulation::new(SEED);
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(200f32, 500f32);
+    simulation.balls[0].velocity = vec2(config::BALL_VELOCITY, 0f32);
+
+    let mut ball = ball::Ball::new(&config::GameConfig::default());
+    ball.freeze_time = None;
+    ball.position = vec2(400f32, 500f32);
+    ball.velocity = vec2(-config::BALL_VELOCITY, 0f32);
+    simulation.balls.push(ball);
+
+    run(&mut simulation, &Input::default(), 0.15);
+
+    assert!(simulation.balls[0].velocity.x < 0f32);
+    assert!(simulation.balls[1].velocity.x > 0f32);
+    assert!(simulation.balls[0].position.x < simulation.balls[1].position.x);
+
+    let distance = glm::distance(&simulation.balls[0].position, &simulation.balls[1].position);
+    assert!(distance >= simulation.balls[0].size + simulation.balls[1].size);
+}
+
+#[test]
+fn test_life_lost_with_last_ball() {
+    let mut simulation = Simulation::with_mode(SEED, GameMode::Chaos);
+
+    assert_eq!(config::CHAOS_NUM_BALLS as usize, simulation.balls.len());
+
+    for ball in &mut simulation.balls[1..] {
+        ball.freeze_time = None;
+        ball.position = vec2(100f32, config::GAME_AREA_HEIGHT as f32 - 10f32);
+        ball.velocity = vec2(0f32, config::BALL_VELOCITY);
+    }
+
+    run(&mut simulation, &Input::default(), 0.1);
+
+    assert_eq!(1, simulation.balls.len());
+    assert_eq!(config::NUM_LIVES, simulation.lives);
+
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(100f32, config::GAME_AREA_HEIGHT as f32 - 10f32);
+    simulation.balls[0].velocity = vec2(0f32, config::BALL_VELOCITY);
+
+    run(&mut simulation, &Input::default(), 0.1);
+
+    assert_eq!(config::NUM_LIVES - 1, simulation.lives);
+    assert_eq!(config::CHAOS_NUM_BALLS as usize, simulation.balls.len());
+}
+
+#[test]
+fn test_paused_simulation_does_not_advance() {
+    let mut simulation = Simulation::new(SEED);
+
+    run(&mut simulation, &Input::default(), 1.5);
+
+    simulation.pause();
+    assert_eq!(GameStage::Paused, simulation.stage);
+
+    let time = simulation.time;
+    let position = simulation.balls[0].position;
+    let bat_position = simulation.bat.position;
+
+    run(&mut simulation, &Input { direction: 1f32 }, 1.0);
+
+    assert_eq!(time, simulation.time);
+    assert_eq!(position, simulation.balls[0].position);
+    assert_eq!(bat_position, simulation.bat.position);
+
+    simulation.resume();
+    assert_eq!(GameStage::Gameplay, simulation.stage);
+
+    run(&mut simulation, &Input::default(), 0.1);
+
+    assert_ne!(position, simulation.balls[0].position);
+}
+
+#[test]
+fn test_pause_only_during_gameplay() {
+    let mut simulation = Simulation::new(SEED);
+    simulation.stage = GameStage::GameOver;
+
+    simulation.pause();
+    assert_eq!(GameStage::GameOver, simulation.stage);
+
+    simulation.resume();
+    assert_eq!(GameStage::GameOver, simulation.stage);
+}
+
+#[test]
+fn test_impacts_of_last_step() {
+    let mut simulation = Simulation::new(SEED);
+    simulation.balls[0].freeze_time = None;
+    simulation.balls[0].position = vec2(config::BALL_START_SIZE + 5f32, 500f32);
+    simulation.balls[0].velocity = vec2(-config::BALL_VELOCITY, 0f32);
+
+    step(&mut simulation, &Input::default()).unwrap();
+
+    assert_eq!(1, simulation.impacts.len());
+    assert_eq!(ball::ImpactKind::Wall, simulation.impacts[0].kind);
+    assert_eq!(vec2(1f32, 0f32), simulation.impacts[0].normal);
+    assert_eq!(0f32, simulation.impacts[0].position.x);
+
+    step(&mut simulation, &Input::default()).unwrap();
+
+    assert!(simulation.impacts.is_empty());
+}
+
+#[test]
+fn test_ball_trail() {
+    let mut ball = ball::Ball::new(&config::GameConfig::default());
+
+    for i in 0..(config::BALL_TRAIL_LENGTH + 3) {
+        ball.position = vec2(i as f32, 0f32);
+        ball.record_trail();
+    }
+
+    let expected : Vec<_> = (3..(config::BALL_TRAIL_LENGTH + 3)).map(|i| vec2(i as f32, 0f32)).collect();
+    assert_eq!(expected, ball.trail());
+
+    ball.reset_position(&config::GameConfig::default(), 0.5f32, config::BALL_VELOCITY);
+    assert!(ball.trail().is_empty());
+}